@@ -2,7 +2,7 @@ use std::fmt::{Display, Formatter};
 
 use super::DbgDisplay;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CodeLocation {
     pub line: usize,
     pub display: DbgDisplay,
@@ -1,10 +1,14 @@
-use std::fmt::{Debug, Display, Formatter, Result as FmtResult};
+use std::{
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    rc::Rc,
+};
 
-pub struct DbgDisplay(Box<dyn Display>);
+#[derive(Clone)]
+pub struct DbgDisplay(Rc<dyn Display>);
 
 impl DbgDisplay {
     pub fn from<T: Display + Clone + 'static>(item: &T) -> Self {
-        Self(Box::new(item.clone()))
+        Self(Rc::new(item.clone()))
     }
 }
 
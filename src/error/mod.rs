@@ -1,5 +1,7 @@
 mod code_location;
 mod dbg_display;
+mod runtime_error;
 
 pub use code_location::*;
 pub use dbg_display::*;
+pub use runtime_error::*;
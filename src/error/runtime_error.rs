@@ -0,0 +1,55 @@
+use std::{
+    fmt::{Display, Formatter},
+    rc::Rc,
+};
+
+use crate::types::{Lit, Tok};
+
+use super::{CodeLocation, DbgDisplay};
+
+/// A failure while running a script. The interpreter raises these for problems like type mismatches
+/// and undefined variables, and `throw` raises one holding the thrown value.
+/// It unwinds as the error of every evaluator and interpreter result until a `catch` handles it.
+#[derive(Debug, Clone)]
+pub struct RuntimeError {
+    pub message: String,
+    pub location: CodeLocation,
+    /// The value given to `throw`. None for errors raised by the interpreter itself.
+    pub thrown: Option<Lit>,
+}
+
+impl RuntimeError {
+    /// An error raised by the interpreter, located at the given token.
+    pub fn new(message: impl Into<String>, token: &Tok) -> Self {
+        Self {
+            message: message.into(),
+            location: CodeLocation {
+                line: token.line,
+                display: DbgDisplay::from(token),
+            },
+            thrown: None,
+        }
+    }
+
+    /// An error raised by a script's `throw`, located at the keyword.
+    pub fn thrown(value: Lit, keyword: &Tok) -> Self {
+        Self {
+            thrown: Some(value.clone()),
+            ..Self::new(format!("Uncaught {}.", value), keyword)
+        }
+    }
+
+    /// What a `catch` binds: the thrown value, or the error itself as a value for errors raised by the interpreter.
+    pub fn into_value(mut self) -> Lit {
+        match self.thrown.take() {
+            Some(value) => value,
+            None => Lit::Error(Rc::new(self)),
+        }
+    }
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{} {}.", self.message, self.location)
+    }
+}
@@ -1,15 +1,29 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
 
 use crate::types::Lit;
 
+/// A single scope. Scopes are shared behind `Rc<RefCell<_>>` so a closure can keep its
+/// declaring scope alive after the block or call that created it has finished.
+#[derive(Default)]
 pub struct Environment {
     values: HashMap<String, Lit>,
+    /// The scope this one is nested in. Lookups that miss in `values` continue here.
+    enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
 impl Environment {
     pub fn new() -> Self {
         Self {
             values: HashMap::new(),
+            enclosing: None,
+        }
+    }
+
+    /// Creates an empty scope nested inside `enclosing`.
+    pub fn new_enclosed(enclosing: Rc<RefCell<Environment>>) -> Self {
+        Self {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
         }
     }
 
@@ -17,7 +31,44 @@ impl Environment {
         self.values.insert(name.into(), value);
     }
 
-    pub fn get(&mut self, name: &str) -> Option<&Lit> {
-        self.values.get(name)
+    /// Overwrites an existing variable in the scope `depth` scopes out from this one, or in the global scope
+    /// if the depth is None. Returns false if that scope does not declare the variable.
+    pub fn assign_at(&mut self, depth: Option<usize>, name: &str, value: Lit) -> bool {
+        match (depth, &self.enclosing) {
+            (Some(0), _) | (None, None) => match self.values.get_mut(name) {
+                Some(existing) => {
+                    *existing = value;
+
+                    true
+                }
+                None => false,
+            },
+            (depth, Some(enclosing)) => {
+                enclosing
+                    .borrow_mut()
+                    .assign_at(depth.map(|depth| depth - 1), name, value)
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Reads a variable from the scope `depth` scopes out from this one, or from the global scope
+    /// if the depth is None. The parser works the depth out, so a function always sees the variable
+    /// that was visible where it was declared, even if a later declaration shadows it.
+    pub fn get_at(&self, depth: Option<usize>, name: &str) -> Option<Lit> {
+        match (depth, &self.enclosing) {
+            (Some(0), _) | (None, None) => self.values.get(name).cloned(),
+            (depth, Some(enclosing)) => enclosing
+                .borrow()
+                .get_at(depth.map(|depth| depth - 1), name),
+            (Some(_), None) => None,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Lit> {
+        match self.values.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self.enclosing.as_ref()?.borrow().get(name),
+        }
     }
 }
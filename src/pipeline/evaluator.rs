@@ -1,8 +1,16 @@
 // Yeah, this could be broken up. But I'm lazy, and it works, and I will refactor it later.
 
-use crate::types::{Expr, Lit, TokType};
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
 
-use super::environment::Environment;
+use crate::{
+    error::RuntimeError,
+    types::{
+        apply_arithmetic, bitwise_not, compare_numbers, negate, Argument, Arguments, EnumValue,
+        Expr, Function, Instance, Lit, Map, MapKey, Tok, TokType,
+    },
+};
+
+use super::{environment::Environment, interpreter::Interpreter};
 
 pub struct Evaluator<'a> {
     expression: &'a Expr,
@@ -16,112 +24,630 @@ impl<'a> Evaluator<'a> {
     pub fn evaluate(
         &self,
         expr: Option<&'a Expr>,
-        environment: &mut Environment,
-    ) -> Result<Lit, String> {
-        let current_expr = expr.unwrap_or(&self.expression);
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<Lit, RuntimeError> {
+        let current_expr = expr.unwrap_or(self.expression);
 
         let value: Lit = match current_expr {
-            Expr::Variable { name } => match (*environment).get(&name.lexeme) {
-                Some(value) => (*value).clone(),
-                None => panic!("A variable has been used that has not been defined."),
-            },
-            Expr::Literal { value } => value.clone(),
-            Expr::Grouping { expression } => self.evaluate(Some(expression), environment)?.clone(),
-            Expr::Unary { operator, right } => {
-                let right = self.evaluate(Some(&right), environment)?;
-
-                if let Lit::Number(num) = right {
-                    return match operator.token_type {
-                        TokType::Minus => Ok(Lit::Number(-num)),
-                        TokType::Plus => Ok(Lit::Number(num)),
-                        TokType::Bang => Ok(Lit::Bool(num == 0.0)),
-                        _ => Err(
-                            "Unexpected token type when evaluating unary for number evaluation."
-                                .to_string(),
-                        ),
-                    };
+            Expr::Assign { name, value, depth } => {
+                let value = self.evaluate(Some(value), environment)?;
+
+                if !environment
+                    .borrow_mut()
+                    .assign_at(*depth, &name.lexeme, value.clone())
+                {
+                    return Err(RuntimeError::new(
+                        "Cannot assign to an undefined variable.",
+                        name,
+                    ));
                 }
 
-                if let Lit::Bool(boolean) = right {
-                    return match operator.token_type {
-                        TokType::Bang => Ok(Lit::Bool(!boolean)),
-                        _ => Err(
-                            "Unexpected token type when evaluating unary for boolean evaluation."
-                                .to_string(),
-                        ),
-                    };
+                value
+            }
+            Expr::AssignPattern {
+                pattern,
+                equals,
+                value,
+                depths,
+            } => {
+                let value = self.evaluate(Some(value), environment)?;
+                let mut bindings = vec![];
+
+                pattern.destructure(&value, &environment.borrow(), &mut bindings, equals)?;
+
+                // Every target is checked before any is assigned, so a failure changes nothing
+                if let Some(((name, _), _)) = bindings
+                    .iter()
+                    .zip(depths)
+                    .find(|((name, _), depth)| environment.borrow().get_at(**depth, name).is_none())
+                {
+                    return Err(RuntimeError::new(
+                        format!("Cannot assign to an undefined variable {}.", name),
+                        equals,
+                    ));
                 }
 
-                if let Lit::Nil = right {
-                    return Err("Illegal use of nil in unary.".to_string());
+                for ((name, item), depth) in bindings.into_iter().zip(depths) {
+                    environment.borrow_mut().assign_at(*depth, &name, item);
                 }
 
-                if let Lit::String(_) = right {
-                    return Err("Illegal use of string in unary.".to_string());
+                value
+            }
+            Expr::Variable { name, depth } => {
+                match environment.borrow().get_at(*depth, &name.lexeme) {
+                    Some(value) => value,
+                    None => {
+                        return Err(RuntimeError::new(
+                            "A variable has been used that has not been defined.",
+                            name,
+                        ))
+                    }
                 }
+            }
+            Expr::Call { .. }
+            | Expr::Get { .. }
+            | Expr::OptionalGet { .. }
+            | Expr::Index { .. } => self
+                .evaluate_chain(current_expr, environment)?
+                .unwrap_or(Lit::Nil),
+            Expr::Set {
+                object,
+                name,
+                value,
+            } => {
+                let Lit::Instance(instance) = self.evaluate(Some(object), environment)? else {
+                    return Err(RuntimeError::new("Only instances have fields.", name));
+                };
+
+                let value = self.evaluate(Some(value), environment)?;
 
-                return Err("Unexpected and/or unidentifiable literal type.".to_string());
+                instance.set(&name.lexeme, value.clone());
+
+                value
             }
-            Expr::Binary {
+            Expr::Super {
+                keyword,
+                method,
+                depth,
+            } => {
+                let superclass = environment.borrow().get_at(*depth, &keyword.lexeme);
+                let instance = environment
+                    .borrow()
+                    .get_at(depth.map(|depth| depth - 1), "this");
+
+                let (Some(Lit::Class(superclass)), Some(Lit::Instance(instance))) =
+                    (superclass, instance)
+                else {
+                    return Err(RuntimeError::new("Super used outside of a method.", method));
+                };
+
+                let method = superclass
+                    .find_method(&method.lexeme)
+                    .ok_or_else(|| RuntimeError::new("Undefined superclass method.", method))?;
+
+                Lit::Function(Rc::new(method.bind(instance)))
+            }
+            Expr::This { keyword, depth } => {
+                environment
+                    .borrow()
+                    .get_at(*depth, &keyword.lexeme)
+                    .ok_or_else(|| RuntimeError::new("This used outside of a method.", keyword))?
+            }
+            Expr::Interpolation { parts } => {
+                let mut string = String::new();
+
+                for part in parts {
+                    string.push_str(&self.evaluate(Some(part), environment)?.to_string());
+                }
+
+                Lit::String(string)
+            }
+            Expr::IndexSet {
+                object,
+                bracket,
+                index,
+                value,
+            } => {
+                let object = self.evaluate(Some(object), environment)?;
+                let index = self.evaluate(Some(index), environment)?;
+                let value = self.evaluate(Some(value), environment)?;
+
+                match object {
+                    Lit::List(list) => {
+                        let mut list = list.borrow_mut();
+                        let position = resolve_index(&index, list.len(), bracket)?;
+
+                        list[position] = value.clone();
+                    }
+                    Lit::Map(map) => map
+                        .borrow_mut()
+                        .insert(resolve_key(&index, bracket)?, value.clone()),
+                    _ => return Err(not_indexable(bracket)),
+                }
+
+                value
+            }
+            Expr::List { elements } => {
+                let mut values: Vec<Lit> = vec![];
+
+                for element in elements {
+                    values.push(self.evaluate(Some(element), environment)?);
+                }
+
+                Lit::List(Rc::new(RefCell::new(values)))
+            }
+            Expr::Map { brace, entries } => {
+                let mut map = Map::new();
+
+                for (key, value) in entries {
+                    let key = resolve_key(&self.evaluate(Some(key), environment)?, brace)?;
+
+                    map.insert(key, self.evaluate(Some(value), environment)?);
+                }
+
+                Lit::Map(Rc::new(RefCell::new(map)))
+            }
+            Expr::Literal { value } => value.clone(),
+            Expr::Lambda {
+                keyword,
+                params,
+                body,
+            } => Lit::Function(Rc::new(Function {
+                name: Tok {
+                    lexeme: "anonymous".to_string(),
+                    ..keyword.clone()
+                },
+                params: params.clone(),
+                body: Rc::clone(body),
+                closure: Rc::clone(environment),
+                is_initializer: false,
+            })),
+            Expr::Logical {
                 left,
                 operator,
                 right,
             } => {
-                let binary_op = (
-                    self.evaluate(Some(&left), environment)?,
-                    operator,
-                    self.evaluate(Some(&right), environment)?,
-                );
-
-                if operator.token_type == TokType::EqualEqual {
-                    return Ok(Lit::Bool(binary_op.0 == binary_op.2));
+                let left = self.evaluate(Some(left), environment)?;
+
+                // The deciding operand is returned as is, so `nil or "anon"` gives "anon" rather than true
+                let decided = match operator.token_type {
+                    TokType::Or => left.is_truthy(),
+                    TokType::QuestionQuestion => left != Lit::Nil,
+                    _ => !left.is_truthy(),
+                };
+
+                if decided {
+                    left
+                } else {
+                    self.evaluate(Some(right), environment)?
+                }
+            }
+            Expr::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                if self.evaluate(Some(condition), environment)?.is_truthy() {
+                    self.evaluate(Some(then_branch), environment)?
+                } else {
+                    self.evaluate(Some(else_branch), environment)?
                 }
+            }
+            Expr::Match {
+                keyword,
+                subject,
+                arms,
+            } => {
+                let subject = self.evaluate(Some(subject), environment)?;
+                let mut matched = None;
+
+                for arm in arms {
+                    let mut bindings = vec![];
+
+                    if !arm
+                        .pattern
+                        .matches(&subject, &environment.borrow(), &mut bindings)?
+                    {
+                        continue;
+                    }
+
+                    // Bindings are scoped to the arm's guard and body
+                    let scope =
+                        Rc::new(RefCell::new(Environment::new_enclosed(environment.clone())));
 
-                if operator.token_type == TokType::BangEqual {
-                    return Ok(Lit::Bool(binary_op.0 != binary_op.2));
+                    for (name, value) in bindings {
+                        scope.borrow_mut().define(&name, value);
+                    }
+
+                    if let Some(guard) = &arm.guard {
+                        if !self.evaluate(Some(guard), &scope)?.is_truthy() {
+                            continue;
+                        }
+                    }
+
+                    matched = Some(self.evaluate(Some(&arm.body), &scope)?);
+
+                    break;
                 }
 
-                return match binary_op {
-                    (Lit::Number(l), operator, Lit::Number(r)) => match operator.token_type {
-                        TokType::Plus => Ok(Lit::Number(l + r)),
-                        TokType::Minus => Ok(Lit::Number(l - r)),
-                        TokType::Star => Ok(Lit::Number(l * r)),
-                        TokType::Slash => Ok(Lit::Number(l / r)),
-                        TokType::Greater => Ok(Lit::Bool(l > r)),
-                        TokType::GreaterEqual => Ok(Lit::Bool(l >= r)),
-                        TokType::Less => Ok(Lit::Bool(l < r)),
-                        TokType::LessEqual => Ok(Lit::Bool(l <= r)),
-                        _ => Err(
-                            "Unexpected token type when evaluating binary for number evaluation."
-                                .to_string(),
-                        ),
-                    },
-                    (Lit::String(l), operator, Lit::String(r)) => match operator.token_type {
-                        TokType::Plus => Ok(Lit::String(format!("{}{}", l, r))),
-                        _ => Err(
-                            "Unexpected token type when evaluating binary for string evaluation."
-                                .to_string(),
-                        ),
-                    },
-                    (Lit::Bool(_), operator, Lit::Bool(_)) => match operator.token_type {
-                        _ => Err(
-                            "Unexpected token type when evaluating binary for boolean evaluation."
-                                .to_string(),
-                        ),
-                    },
-                    (Lit::Nil, operator, Lit::Nil) => match operator.token_type {
-                        _ => Err(
-                            "Unexpected token type when evaluating binary for nil evaluation."
-                                .to_string(),
-                        ),
+                matched.ok_or_else(|| {
+                    RuntimeError::new(format!("No match arm matches {}.", subject), keyword)
+                })?
+            }
+            Expr::Grouping { expression } => self.evaluate(Some(expression), environment)?.clone(),
+            Expr::Unary { operator, right } => {
+                let right = self.evaluate(Some(right), environment)?;
+
+                if operator.token_type == TokType::Bang {
+                    return Ok(Lit::Bool(!right.is_truthy()));
+                }
+
+                let message = if right.is_number() {
+                    let result = match operator.token_type {
+                        TokType::Minus => negate(&right),
+                        TokType::Tilde => bitwise_not(&right),
+                        _ => Ok(right),
+                    };
+
+                    return result.map_err(|message| RuntimeError::new(message, operator));
+                } else if let Lit::Bool(_) = right {
+                    "Unexpected token type when evaluating unary for boolean evaluation."
+                } else if let Lit::Nil = right {
+                    "Illegal use of nil in unary."
+                } else if let Lit::String(_) = right {
+                    "Illegal use of string in unary."
+                } else {
+                    "Unexpected and/or unidentifiable literal type."
+                };
+
+                return Err(RuntimeError::new(message, operator));
+            }
+            Expr::Binary {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(Some(left), environment)?;
+                let right = self.evaluate(Some(right), environment)?;
+
+                binary(left, operator, right)?
+            }
+            Expr::Update {
+                target,
+                operator,
+                value,
+                postfix,
+            } => {
+                // `x += 1` applies `+`, located at the `+=`
+                let operator = Tok {
+                    token_type: match operator.token_type {
+                        TokType::PlusEqual | TokType::PlusPlus => TokType::Plus,
+                        TokType::MinusEqual | TokType::MinusMinus => TokType::Minus,
+                        TokType::StarEqual => TokType::Star,
+                        TokType::SlashEqual => TokType::Slash,
+                        _ => TokType::Percent,
                     },
-                    _ => Err("Unexpected and unidentifiable literal type.".to_string()),
+                    ..operator.clone()
                 };
+
+                // The target's own parts have been evaluated before this runs, and are not evaluated again
+                let combine = |old: &Lit| {
+                    let value = self.evaluate(Some(value), environment)?;
+
+                    binary(old.clone(), &operator, value)
+                };
+
+                let (old, new) = match target.as_ref() {
+                    Expr::Variable { name, depth } => {
+                        let old = self.evaluate(Some(target), environment)?;
+                        let new = combine(&old)?;
+
+                        environment
+                            .borrow_mut()
+                            .assign_at(*depth, &name.lexeme, new.clone());
+
+                        (old, new)
+                    }
+                    Expr::Get { object, name } => {
+                        let Lit::Instance(instance) = self.evaluate(Some(object), environment)?
+                        else {
+                            return Err(RuntimeError::new("Only instances have fields.", name));
+                        };
+
+                        let old = Instance::get(&instance, &name.lexeme)
+                            .ok_or_else(|| RuntimeError::new("Undefined property.", name))?;
+                        let new = combine(&old)?;
+
+                        instance.set(&name.lexeme, new.clone());
+
+                        (old, new)
+                    }
+                    Expr::Index {
+                        object,
+                        bracket,
+                        index,
+                    } => {
+                        let object = self.evaluate(Some(object), environment)?;
+                        let index = self.evaluate(Some(index), environment)?;
+
+                        match object {
+                            Lit::List(list) => {
+                                let old = {
+                                    let list = list.borrow();
+
+                                    list[resolve_index(&index, list.len(), bracket)?].clone()
+                                };
+                                let new = combine(&old)?;
+
+                                // Resolved again, as evaluating the value may have resized the list
+                                let mut list = list.borrow_mut();
+                                let position = resolve_index(&index, list.len(), bracket)?;
+
+                                list[position] = new.clone();
+
+                                (old, new)
+                            }
+                            Lit::Map(map) => {
+                                let key = resolve_key(&index, bracket)?;
+                                let old = map.borrow().get(&key).cloned().unwrap_or(Lit::Nil);
+                                let new = combine(&old)?;
+
+                                map.borrow_mut().insert(key, new.clone());
+
+                                (old, new)
+                            }
+                            _ => return Err(not_indexable(bracket)),
+                        }
+                    }
+                    _ => return Err(RuntimeError::new("Invalid assignment target.", &operator)),
+                };
+
+                if *postfix {
+                    old
+                } else {
+                    new
+                }
             }
         };
 
         Ok(value)
     }
+
+    /// Evaluates a link in a chain of calls, property accesses and indexes, like `a.b(c)[0]`.
+    /// Returns None when a `?.` earlier in the chain found nil, so that the rest of the chain is skipped.
+    fn evaluate_chain(
+        &self,
+        expr: &'a Expr,
+        environment: &Rc<RefCell<Environment>>,
+    ) -> Result<Option<Lit>, RuntimeError> {
+        let value = match expr {
+            Expr::Call {
+                callee,
+                paren,
+                args,
+            } => {
+                let Some(callee) = self.evaluate_chain(callee, environment)? else {
+                    return Ok(None);
+                };
+                let mut arguments = Arguments::default();
+
+                for arg in args {
+                    match arg {
+                        Argument::Positional(value) => arguments
+                            .positional
+                            .push(self.evaluate(Some(value), environment)?),
+                        Argument::Named { name, value } => arguments
+                            .named
+                            .push((name.clone(), self.evaluate(Some(value), environment)?)),
+                        Argument::Spread(value) => match self.evaluate(Some(value), environment)? {
+                            Lit::List(list) => {
+                                arguments.positional.extend(list.borrow().iter().cloned())
+                            }
+                            value => {
+                                return Err(RuntimeError::new(
+                                    format!("Only lists can be spread, not {}.", value),
+                                    paren,
+                                ))
+                            }
+                        },
+                    }
+                }
+
+                match callee {
+                    Lit::Function(function) => {
+                        Interpreter::call_function(&function, arguments, paren)?
+                    }
+                    Lit::Class(class) => Interpreter::instantiate(&class, arguments, paren)?,
+                    Lit::Variant(variant) => {
+                        let name = format!("{}.{}", variant.enum_name, variant.name);
+
+                        // Variant fields have no defaults, so every value is given
+                        let values = arguments
+                            .bind(&name, &variant.fields)
+                            .map_err(|message| RuntimeError::new(message, paren))?
+                            .into_iter()
+                            .map(|value| value.unwrap_or(Lit::Nil))
+                            .collect();
+
+                        Lit::EnumValue(Rc::new(EnumValue { variant, values }))
+                    }
+                    _ => {
+                        return Err(RuntimeError::new(
+                            format!(
+                                "Can only call functions, classes and enum variants, not {}.",
+                                callee
+                            ),
+                            paren,
+                        ))
+                    }
+                }
+            }
+            Expr::Get { object, name } => {
+                let Some(object) = self.evaluate_chain(object, environment)? else {
+                    return Ok(None);
+                };
+
+                get_property(object, name)?
+            }
+            Expr::OptionalGet { object, name } => match self.evaluate_chain(object, environment)? {
+                None | Some(Lit::Nil) => return Ok(None),
+                Some(object) => get_property(object, name)?,
+            },
+            Expr::Index {
+                object,
+                bracket,
+                index,
+            } => {
+                let Some(object) = self.evaluate_chain(object, environment)? else {
+                    return Ok(None);
+                };
+                let index = self.evaluate(Some(index), environment)?;
+
+                match object {
+                    Lit::List(list) => {
+                        let list = list.borrow();
+
+                        list[resolve_index(&index, list.len(), bracket)?].clone()
+                    }
+                    // A missing key reads as nil so lookups can be defaulted with `or`
+                    Lit::Map(map) => map
+                        .borrow()
+                        .get(&resolve_key(&index, bracket)?)
+                        .cloned()
+                        .unwrap_or(Lit::Nil),
+                    _ => return Err(not_indexable(bracket)),
+                }
+            }
+            _ => self.evaluate(Some(expr), environment)?,
+        };
+
+        Ok(Some(value))
+    }
+}
+
+fn get_property(object: Lit, name: &Tok) -> Result<Lit, RuntimeError> {
+    match object {
+        Lit::Instance(instance) => Instance::get(&instance, &name.lexeme)
+            .ok_or_else(|| RuntimeError::new("Undefined property.", name)),
+        Lit::Enum(enumeration) => enumeration
+            .get(&name.lexeme)
+            .ok_or_else(|| RuntimeError::new("Undefined variant.", name)),
+        Lit::EnumValue(value) => value
+            .get(&name.lexeme)
+            .ok_or_else(|| RuntimeError::new("Undefined field.", name)),
+        // Caught errors expose what went wrong and where
+        Lit::Error(error) => match name.lexeme.as_str() {
+            "message" => Ok(Lit::String(error.message.clone())),
+            "line" => Ok(Lit::Integer(error.location.line as i64)),
+            _ => Err(RuntimeError::new("Undefined property.", name)),
+        },
+        _ => Err(RuntimeError::new("Only instances have properties.", name)),
+    }
+}
+
+/// Applies a binary operator to two evaluated operands.
+fn binary(left: Lit, operator: &Tok, right: Lit) -> Result<Lit, RuntimeError> {
+    let binary_op = (left, operator, right);
+
+    if operator.token_type == TokType::EqualEqual {
+        return Ok(Lit::Bool(binary_op.0 == binary_op.2));
+    }
+
+    if operator.token_type == TokType::BangEqual {
+        return Ok(Lit::Bool(binary_op.0 != binary_op.2));
+    }
+
+    match binary_op {
+        // Integers and floats mix freely, see `types::number`
+        (l, operator, r) if l.is_number() && r.is_number() => {
+            let ordering = compare_numbers(&l, &r);
+
+            match operator.token_type {
+                TokType::Greater => Ok(Lit::Bool(ordering == Some(Ordering::Greater))),
+                TokType::GreaterEqual => Ok(Lit::Bool(matches!(
+                    ordering,
+                    Some(Ordering::Greater | Ordering::Equal)
+                ))),
+                TokType::Less => Ok(Lit::Bool(ordering == Some(Ordering::Less))),
+                TokType::LessEqual => Ok(Lit::Bool(matches!(
+                    ordering,
+                    Some(Ordering::Less | Ordering::Equal)
+                ))),
+                _ => apply_arithmetic(&l, operator.token_type, &r)
+                    .map_err(|message| RuntimeError::new(message, operator)),
+            }
+        }
+        (Lit::String(l), operator, Lit::String(r)) => match operator.token_type {
+            TokType::Plus => Ok(Lit::String(format!("{}{}", l, r))),
+            _ => Err(RuntimeError::new(
+                "Unexpected token type when evaluating binary for string evaluation.",
+                operator,
+            )),
+        },
+        (Lit::Bool(_), operator, Lit::Bool(_)) => Err(RuntimeError::new(
+            "Unexpected token type when evaluating binary for boolean evaluation.",
+            operator,
+        )),
+        (Lit::Nil, operator, Lit::Nil) => Err(RuntimeError::new(
+            "Unexpected token type when evaluating binary for nil evaluation.",
+            operator,
+        )),
+        (_, operator, _) => Err(RuntimeError::new(
+            "Unexpected and unidentifiable literal type.",
+            operator,
+        )),
+    }
+}
+
+fn not_indexable(bracket: &Tok) -> RuntimeError {
+    RuntimeError::new("Only lists and maps can be indexed.", bracket)
+}
+
+fn resolve_key(key: &Lit, token: &Tok) -> Result<MapKey, RuntimeError> {
+    MapKey::from_lit(key).ok_or_else(|| {
+        RuntimeError::new(
+            format!(
+                "Map keys must be strings, numbers, booleans or nil, not {}.",
+                key
+            ),
+            token,
+        )
+    })
+}
+
+/// Turns an index value into a position in a list of the given length.
+/// Negative indices count back from the end, so -1 is the last element.
+fn resolve_index(index: &Lit, len: usize, bracket: &Tok) -> Result<usize, RuntimeError> {
+    let out_of_bounds = || {
+        RuntimeError::new(
+            format!(
+                "List index {} is out of bounds for a list of length {}.",
+                index, len
+            ),
+            bracket,
+        )
+    };
+
+    let integer = match index {
+        Lit::Integer(integer) => *integer,
+        Lit::BigInteger(_) => return Err(out_of_bounds()),
+        Lit::Number(_) => {
+            return Err(RuntimeError::new("List index must be an integer.", bracket));
+        }
+        _ => return Err(RuntimeError::new("List index must be a number.", bracket)),
+    };
+
+    let position = if integer < 0 {
+        len as i64 + integer
+    } else {
+        integer
+    };
+
+    if position < 0 || position >= len as i64 {
+        return Err(out_of_bounds());
+    }
+
+    Ok(position as usize)
 }
 
 // #[cfg(test)]
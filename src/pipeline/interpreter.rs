@@ -1,46 +1,1663 @@
-use crate::types::{Lit, Stmt};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    error::RuntimeError,
+    types::{Arguments, Class, Enum, Function, Instance, Lit, Param, Stmt, Tok, Variant},
+};
 
 use super::{environment::Environment, evaluator::Evaluator};
 
+/// How deeply calls may nest before the script is stopped. Each call takes a good deal of native stack,
+/// so scripts are run on a thread with a stack big enough for this many.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+thread_local! {
+    // the number of calls currently running
+    static CALL_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// How control left a statement. `Break` and `Continue` unwind to the nearest enclosing loop,
+/// `Return` unwinds to the function call.
+enum Flow {
+    Normal,
+    Break,
+    Continue,
+    Return(Lit),
+}
+
 pub struct Interpreter {
-    environment: Environment,
+    environment: Rc<RefCell<Environment>>,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         Self {
-            environment: Environment::new(),
+            environment: Rc::new(RefCell::new(Environment::new())),
         }
     }
 
     #[allow(dead_code)]
-    pub fn interpret_expr(&mut self, evaluator: &mut Evaluator) -> Result<Lit, String> {
-        evaluator.evaluate(None, &mut self.environment)
+    pub fn interpret_expr(&mut self, evaluator: &mut Evaluator) -> Result<Lit, RuntimeError> {
+        evaluator.evaluate(None, &self.environment)
+    }
+
+    pub fn interpret_stmts(&mut self, statements: Vec<Stmt>) -> Result<(), RuntimeError> {
+        for statement in statements.iter() {
+            self.execute(statement)?;
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, statement: &Stmt) -> Result<Flow, RuntimeError> {
+        match statement {
+            Stmt::Block { stmts } => {
+                let environment = Environment::new_enclosed(Rc::clone(&self.environment));
+
+                return self.execute_block(stmts, environment);
+            }
+            Stmt::Break => return Ok(Flow::Break),
+            Stmt::Class {
+                name,
+                superclass,
+                methods,
+            } => {
+                let superclass = match superclass {
+                    Some(expr) => match Evaluator::new(expr).evaluate(None, &self.environment)? {
+                        Lit::Class(class) => Some(class),
+                        _ => return Err(RuntimeError::new("Superclass must be a class.", name)),
+                    },
+                    None => None,
+                };
+
+                // Methods of a subclass close over a scope holding `super`, so `super` always refers
+                // to the superclass of the class the method was declared in.
+                let method_environment = match &superclass {
+                    Some(superclass) => {
+                        let mut environment =
+                            Environment::new_enclosed(Rc::clone(&self.environment));
+
+                        environment.define("super", Lit::Class(Rc::clone(superclass)));
+
+                        Rc::new(RefCell::new(environment))
+                    }
+                    None => Rc::clone(&self.environment),
+                };
+
+                let methods = methods
+                    .iter()
+                    .filter_map(|method| match method {
+                        Stmt::Function { name, params, body } => Some((
+                            name.lexeme.clone(),
+                            Rc::new(Function {
+                                name: name.clone(),
+                                params: params.clone(),
+                                body: Rc::clone(body),
+                                closure: Rc::clone(&method_environment),
+                                is_initializer: name.lexeme == "init",
+                            }),
+                        )),
+                        _ => None,
+                    })
+                    .collect();
+
+                let class = Class {
+                    name: name.lexeme.clone(),
+                    superclass,
+                    methods,
+                };
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Lit::Class(Rc::new(class)));
+            }
+            Stmt::Continue => return Ok(Flow::Continue),
+            Stmt::Enum { name, variants } => {
+                let variants = variants
+                    .iter()
+                    .map(|(variant, fields)| {
+                        Rc::new(Variant {
+                            enum_name: name.lexeme.clone(),
+                            name: variant.lexeme.clone(),
+                            fields: fields.iter().cloned().map(Param::new).collect(),
+                        })
+                    })
+                    .collect();
+
+                let enumeration = Enum {
+                    name: name.lexeme.clone(),
+                    variants,
+                };
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Lit::Enum(Rc::new(enumeration)));
+            }
+            Stmt::Expr { expr } => {
+                let evaluator = Evaluator::new(expr);
+
+                evaluator.evaluate(None, &self.environment)?;
+            }
+            Stmt::Function { name, params, body } => {
+                let function = Function {
+                    name: name.clone(),
+                    params: params.clone(),
+                    body: Rc::clone(body),
+                    closure: Rc::clone(&self.environment),
+                    is_initializer: false,
+                };
+
+                self.environment
+                    .borrow_mut()
+                    .define(&name.lexeme, Lit::Function(Rc::new(function)));
+            }
+            Stmt::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                let evaluator = Evaluator::new(condition);
+
+                if evaluator.evaluate(None, &self.environment)?.is_truthy() {
+                    return self.execute(then_branch);
+                } else if let Some(else_branch) = else_branch {
+                    return self.execute(else_branch);
+                }
+            }
+            Stmt::Print { expr } => {
+                let evaluator = Evaluator::new(expr);
+                let value = evaluator.evaluate(None, &self.environment)?;
+
+                println!("{value}");
+            }
+            Stmt::Return { expr } => {
+                let evaluator = Evaluator::new(expr);
+                let value = evaluator.evaluate(None, &self.environment)?;
+
+                return Ok(Flow::Return(value));
+            }
+            Stmt::Throw { keyword, expr } => {
+                let value = Evaluator::new(expr).evaluate(None, &self.environment)?;
+
+                // Rethrowing a caught error keeps its original message and location
+                return Err(match value {
+                    Lit::Error(error) => (*error).clone(),
+                    value => RuntimeError::thrown(value, keyword),
+                });
+            }
+            Stmt::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let environment = Environment::new_enclosed(Rc::clone(&self.environment));
+                let mut result = self.execute_block(body, environment);
+
+                if let (Err(error), Some((name, handler))) = (&result, catch) {
+                    let mut environment = Environment::new_enclosed(Rc::clone(&self.environment));
+
+                    environment.define(&name.lexeme, error.clone().into_value());
+
+                    result = self.execute_block(handler, environment);
+                }
+
+                if let Some(finally) = finally {
+                    let environment = Environment::new_enclosed(Rc::clone(&self.environment));
+
+                    // An error, break, continue or return in the finally block replaces the outcome of the try
+                    match self.execute_block(finally, environment)? {
+                        Flow::Normal => (),
+                        flow => return Ok(flow),
+                    }
+                }
+
+                return result;
+            }
+            Stmt::Var { name: token, expr } => {
+                let evaluator = Evaluator::new(expr);
+                let value = evaluator.evaluate(Some(expr), &self.environment)?;
+
+                self.environment.borrow_mut().define(&token.lexeme, value);
+            }
+            Stmt::VarPattern {
+                pattern,
+                equals,
+                expr,
+            } => {
+                let value = Evaluator::new(expr).evaluate(None, &self.environment)?;
+                let mut bindings = vec![];
+
+                pattern.destructure(&value, &self.environment.borrow(), &mut bindings, equals)?;
+
+                for (name, value) in bindings {
+                    self.environment.borrow_mut().define(&name, value);
+                }
+            }
+            Stmt::While {
+                condition,
+                body,
+                increment,
+            } => {
+                let condition = Evaluator::new(condition);
+
+                while condition.evaluate(None, &self.environment)?.is_truthy() {
+                    match self.execute(body)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Normal | Flow::Continue => (),
+                    }
+
+                    if let Some(increment) = increment {
+                        Evaluator::new(increment).evaluate(None, &self.environment)?;
+                    }
+                }
+            }
+        }
+
+        Ok(Flow::Normal)
+    }
+
+    /// Binds the arguments to the function's parameters and runs its body.
+    /// Arguments that don't fit the parameters are an error located at the call's closing paren.
+    pub fn call_function(
+        function: &Function,
+        arguments: Arguments,
+        paren: &Tok,
+    ) -> Result<Lit, RuntimeError> {
+        let values = arguments
+            .bind(&function.name.lexeme, &function.params)
+            .map_err(|message| RuntimeError::new(message, paren))?;
+
+        Self::run_function(function, values, paren)
+    }
+
+    /// Runs the function with a value for each parameter, or None where its default is to be used.
+    /// A call nested too deeply is a stack overflow error located at the call's closing paren.
+    fn run_function(
+        function: &Function,
+        values: Vec<Option<Lit>>,
+        paren: &Tok,
+    ) -> Result<Lit, RuntimeError> {
+        let depth = CALL_DEPTH.get();
+
+        if depth >= MAX_CALL_DEPTH {
+            return Err(RuntimeError::new("Stack overflow.", paren));
+        }
+
+        CALL_DEPTH.set(depth + 1);
+
+        let result = Self::run_body(function, values);
+
+        CALL_DEPTH.set(depth);
+
+        result
+    }
+
+    fn run_body(function: &Function, values: Vec<Option<Lit>>) -> Result<Lit, RuntimeError> {
+        // Defaults are evaluated on each call, in a scope holding the parameters before them
+        let parameters = Rc::new(RefCell::new(Environment::new_enclosed(Rc::clone(
+            &function.closure,
+        ))));
+
+        for (param, value) in function.params.iter().zip(values) {
+            let value = match (value, &param.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => Evaluator::new(default).evaluate(None, &parameters)?,
+                // Arguments::bind has already reported missing arguments
+                (None, None) => Lit::Nil,
+            };
+
+            parameters.borrow_mut().define(&param.name.lexeme, value);
+        }
+
+        let mut interpreter = Self {
+            environment: Rc::clone(&function.closure),
+        };
+
+        let flow =
+            interpreter.execute_block(&function.body, Environment::new_enclosed(parameters))?;
+
+        if function.is_initializer {
+            return Ok(function.closure.borrow().get("this").unwrap_or(Lit::Nil));
+        }
+
+        match flow {
+            Flow::Return(value) => Ok(value),
+            _ => Ok(Lit::Nil),
+        }
+    }
+
+    /// Creates an instance and runs the class initializer on it, if there is one.
+    /// The arguments are those of the initializer, and argument errors show the class name in the signature.
+    pub fn instantiate(
+        class: &Rc<Class>,
+        arguments: Arguments,
+        paren: &Tok,
+    ) -> Result<Lit, RuntimeError> {
+        let instance = Rc::new(Instance::new(Rc::clone(class)));
+
+        match class.find_method("init") {
+            Some(initializer) => {
+                let values = arguments
+                    .bind(&class.name, &initializer.params)
+                    .map_err(|message| RuntimeError::new(message, paren))?;
+
+                Self::run_function(&initializer.bind(Rc::clone(&instance)), values, paren)?;
+            }
+            None => {
+                arguments
+                    .bind(&class.name, &[])
+                    .map_err(|message| RuntimeError::new(message, paren))?;
+            }
+        }
+
+        Ok(Lit::Instance(instance))
     }
 
-    pub fn interpret_stmts(&mut self, statements: Vec<Stmt>) -> Result<(), String> {
+    /// Runs the statements in the given scope, then restores the previous scope even if a statement fails.
+    /// Stops early if a statement breaks, continues or returns, handing that flow to the enclosing statement.
+    fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Environment,
+    ) -> Result<Flow, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, Rc::new(RefCell::new(environment)));
+
+        let mut result = Ok(Flow::Normal);
+
         for statement in statements {
-            match statement {
-                Stmt::Expr { expr } => {
-                    let evaluator = Evaluator::new(&expr);
+            result = self.execute(statement);
+
+            if !matches!(result, Ok(Flow::Normal)) {
+                break;
+            }
+        }
+
+        self.environment = previous;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pipeline::{parser::Parser, scanner::Scanner},
+        types::Lit,
+    };
+
+    use super::Interpreter;
+
+    fn try_interpret(source: &str) -> Result<Interpreter, String> {
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let statements = parser.parse().unwrap();
+        let mut interpreter = Interpreter::new();
+
+        interpreter
+            .interpret_stmts(statements)
+            .map_err(|error| error.to_string())?;
+
+        Ok(interpreter)
+    }
+
+    fn interpret(source: &str) -> Interpreter {
+        try_interpret(source).unwrap()
+    }
+
+    #[test]
+    fn should_shadow_then_restore_in_block() {
+        let interpreter = interpret("var a = 1; { var a = 2; var b = a; }");
+
+        assert_eq!(
+            interpreter.environment.borrow().get("a"),
+            Some(Lit::Number(1.0))
+        );
+        assert_eq!(interpreter.environment.borrow().get("b"), None);
+    }
+
+    #[test]
+    fn should_read_enclosing_scope_in_nested_block() {
+        let interpreter = interpret("var a = 1; { { var b = a + 1; print b; } }");
+
+        assert_eq!(
+            interpreter.environment.borrow().get("a"),
+            Some(Lit::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn should_assign_to_enclosing_scope() {
+        let interpreter = interpret("var a = 1; var b; { b = a = a + 1; }");
+
+        assert_eq!(
+            interpreter.environment.borrow().get("a"),
+            Some(Lit::Number(2.0))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("b"),
+            Some(Lit::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn should_error_when_assigning_undefined_variable() {
+        let result = try_interpret("undeclared = 1;");
+
+        assert!(result.is_err_and(|error| error.contains("line 1")));
+    }
+
+    #[test]
+    fn should_take_branch_by_truthiness() {
+        let interpreter = interpret(
+            r#"
+            var a; var b; var c; var d;
+            if (0) a = "then"; else a = "else";
+            if ("") b = "then"; else b = "else";
+            if (nil) c = "then"; else c = "else";
+            if ("text") d = "then"; else d = "else";
+            "#,
+        );
+
+        let branch = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(branch("a"), Some(Lit::String("else".into())));
+        assert_eq!(branch("b"), Some(Lit::String("else".into())));
+        assert_eq!(branch("c"), Some(Lit::String("else".into())));
+        assert_eq!(branch("d"), Some(Lit::String("then".into())));
+    }
+
+    #[test]
+    fn should_negate_with_truthiness() {
+        let interpreter = interpret(r#"var a = !nil; var b = !"text"; var c = !0;"#);
+
+        assert_eq!(
+            interpreter.environment.borrow().get("a"),
+            Some(Lit::Bool(true))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("b"),
+            Some(Lit::Bool(false))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("c"),
+            Some(Lit::Bool(true))
+        );
+    }
+
+    #[test]
+    fn should_loop_with_while() {
+        let interpreter =
+            interpret("var i = 0; var sum = 0; while (i < 4) { i = i + 1; sum = sum + i; }");
+
+        assert_eq!(
+            interpreter.environment.borrow().get("sum"),
+            Some(Lit::Number(10.0))
+        );
+    }
+
+    #[test]
+    fn should_run_increment_on_continue_and_stop_on_break() {
+        let interpreter = interpret(
+            r#"
+            var sum = 0;
+            for (var i = 0; i < 10; i = i + 1) {
+                if (i == 2) continue;
+                if (i == 5) break;
+                sum = sum + i;
+            }
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("sum"),
+            Some(Lit::Number(8.0))
+        );
+        assert_eq!(interpreter.environment.borrow().get("i"), None);
+    }
+
+    #[test]
+    fn should_short_circuit_logical_operators() {
+        let interpreter = interpret(
+            r#"
+            var calls = 0;
+            var name = nil or "anon";
+            var skipped = false and (calls = 1);
+            var kept = 1 or (calls = 2);
+            var last = 1 and "last";
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("name"), Some(Lit::String("anon".into())));
+        assert_eq!(get("skipped"), Some(Lit::Bool(false)));
+        assert_eq!(get("kept"), Some(Lit::Number(1.0)));
+        assert_eq!(get("last"), Some(Lit::String("last".into())));
+        assert_eq!(get("calls"), Some(Lit::Number(0.0)));
+    }
+
+    #[test]
+    fn should_call_function_and_return_value() {
+        let interpreter = interpret(
+            r#"
+            fun fib(n) {
+                if (n < 2) return n;
+                return fib(n - 1) + fib(n - 2);
+            }
+            var result = fib(10);
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("result"),
+            Some(Lit::Number(55.0))
+        );
+    }
 
-                    evaluator.evaluate(None, &mut self.environment)?;
+    #[test]
+    fn should_return_from_inside_loop() {
+        let interpreter = interpret(
+            r#"
+            fun firstOver(limit) {
+                for (var i = 0; ; i = i + 1) {
+                    if (i > limit) return i;
                 }
-                Stmt::Print { expr } => {
-                    let evaluator = Evaluator::new(&expr);
-                    let value = evaluator.evaluate(None, &mut self.environment)?;
+            }
+            fun nothing() {}
+            var found = firstOver(3);
+            var empty = nothing();
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("found"),
+            Some(Lit::Number(4.0))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("empty"),
+            Some(Lit::Nil)
+        );
+    }
+
+    #[test]
+    fn should_error_on_arity_mismatch() {
+        let result = try_interpret("fun pair(a, b) {} pair(1);");
+
+        assert!(
+            result.is_err_and(|error| error.contains("Missing argument b. Expected pair(a, b)."))
+        );
+    }
 
-                    println!("{value}");
+    #[test]
+    fn should_error_when_calling_non_callable() {
+        let result = try_interpret(r#""text"();"#);
+
+        assert!(result.is_err_and(|error| error.contains("Can only call functions")));
+    }
+
+    #[test]
+    fn should_capture_declaring_scope_in_closure() {
+        let interpreter = interpret(
+            r#"
+            fun makeCounter() {
+                var i = 0;
+                fun inc() {
+                    i = i + 1;
+                    return i;
                 }
-                Stmt::Var { name: token, expr } => {
-                    let evaluator = Evaluator::new(&expr);
-                    let value = evaluator.evaluate(Some(&expr), &mut self.environment)?;
+                return inc;
+            }
+            var counter = makeCounter();
+            var other = makeCounter();
+            counter();
+            var second = counter();
+            var separate = other();
+            "#,
+        );
 
-                    self.environment.define(&token.lexeme, value);
+        assert_eq!(
+            interpreter.environment.borrow().get("second"),
+            Some(Lit::Number(2.0))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("separate"),
+            Some(Lit::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn should_keep_seeing_declared_variable_after_later_shadowing() {
+        let interpreter = interpret(
+            r#"
+            var a = "global";
+            var b = "global";
+            var seen = [nil, nil];
+            var kept;
+            {
+                fun show() { return a; }
+                fun set() { b = "set"; }
+                seen[0] = show();
+                var a = "block";
+                var b = "block";
+                seen[1] = show();
+                set();
+                kept = b;
+            }
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("seen").unwrap().to_string(), r#"["global", "global"]"#);
+        assert_eq!(get("b"), Some(Lit::String("set".into())));
+        assert_eq!(get("kept"), Some(Lit::String("block".into())));
+    }
+
+    #[test]
+    fn should_call_sibling_local_functions_declared_later() {
+        let interpreter = interpret(
+            r#"
+            var even;
+            {
+                fun isEven(n) { if (n == 0) return true; return isOdd(n - 1); }
+                fun isOdd(n) { if (n == 0) return false; return isEven(n - 1); }
+                even = isEven(4);
+            }
+            fun outer() {
+                fun a() { return b(); }
+                fun b() { return "b"; }
+                return a();
+            }
+            var called = outer();
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("even"), Some(Lit::Bool(true)));
+        assert_eq!(get("called"), Some(Lit::String("b".into())));
+    }
+
+    #[test]
+    fn should_not_see_caller_scope() {
+        let result = try_interpret(
+            r#"
+            fun peek() { return hidden; }
+            fun caller() { var hidden = 1; return peek(); }
+            caller();
+            "#,
+        );
+
+        assert!(result.is_err_and(|error| error.contains("not been defined")));
+    }
+
+    #[test]
+    fn should_construct_instance_with_fields_and_methods() {
+        let interpreter = interpret(
+            r#"
+            class Point {
+                init(x, y) {
+                    this.x = x;
+                    this.y = y;
+                }
+                sum() {
+                    return this.x + this.y;
                 }
             }
+            var point = Point(1, 2);
+            point.y = 10;
+            var sum = point.sum();
+            var printed = point.x;
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("sum"),
+            Some(Lit::Number(11.0))
+        );
+        assert_eq!(
+            interpreter.environment.borrow().get("printed"),
+            Some(Lit::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn should_keep_method_bound_to_instance() {
+        let interpreter = interpret(
+            r#"
+            class Named {
+                init(name) { this.name = name; }
+                get() { return this.name; }
+            }
+            var getter = Named("first").get;
+            var name = getter();
+            var reinit = Named("a").init("b");
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("name"),
+            Some(Lit::String("first".into()))
+        );
+        assert!(matches!(
+            interpreter.environment.borrow().get("reinit"),
+            Some(Lit::Instance(_))
+        ));
+    }
+
+    #[test]
+    fn should_error_on_undefined_property() {
+        let result = try_interpret("class Empty {} Empty().missing;");
+
+        assert!(result.is_err_and(|error| error.contains("Undefined property")));
+    }
+
+    #[test]
+    fn should_inherit_and_call_super() {
+        let interpreter = interpret(
+            r#"
+            class Shape {
+                init(name) { this.name = name; }
+                describe() { return "shape " + this.name; }
+                kind() { return "shape"; }
+            }
+            class Square < Shape {
+                init(size) {
+                    super.init("square");
+                    this.size = size;
+                }
+                describe() { return super.describe() + "!"; }
+            }
+            class Tiny < Square {}
+            var square = Tiny(2);
+            var description = square.describe();
+            var kind = square.kind();
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(
+            get("description"),
+            Some(Lit::String("shape square!".into()))
+        );
+        assert_eq!(get("kind"), Some(Lit::String("shape".into())));
+    }
+
+    #[test]
+    fn should_error_when_inheriting_from_non_class() {
+        let result = try_interpret("var NotClass = 1; class A < NotClass {}");
+
+        assert!(result.is_err_and(|error| error.contains("Superclass must be a class")));
+    }
+
+    #[test]
+    fn should_index_and_assign_list_elements() {
+        let interpreter = interpret(
+            r#"
+            var xs = [1, 2, [3, 4]];
+            var alias = xs;
+            alias[0] = "first";
+            xs[-1][-2] = 30;
+            var first = xs[0];
+            var nested = xs[2][0];
+            var last = xs[-2];
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("first"), Some(Lit::String("first".into())));
+        assert_eq!(get("nested"), Some(Lit::Number(30.0)));
+        assert_eq!(get("last"), Some(Lit::Number(2.0)));
+        assert_eq!(get("xs").unwrap().to_string(), r#"["first", 2, [30, 4]]"#);
+    }
+
+    #[test]
+    fn should_error_on_out_of_bounds_index() {
+        let high = try_interpret("var xs = [1, 2]; xs[2];");
+        let low = try_interpret("var xs = [1, 2]; xs[-3] = 0;");
+        let fraction = try_interpret("var xs = [1, 2]; xs[0.5];");
+
+        assert!(high.is_err_and(|error| error.contains("out of bounds")));
+        assert!(low.is_err_and(|error| error.contains("out of bounds")));
+        assert!(fraction.is_err_and(|error| error.contains("must be an integer")));
+    }
+
+    #[test]
+    fn should_build_and_update_map_in_insertion_order() {
+        let interpreter = interpret(
+            r#"
+            var m = {"b": 1, "a": 2, 3: [true]};
+            m["c"] = 3;
+            m["b"] = m["b"] + 10;
+            var missing = m["zzz"];
+            var number = m[3][0];
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(
+            get("m").unwrap().to_string(),
+            r#"{"b": 11, "a": 2, 3: [true], "c": 3}"#
+        );
+        assert_eq!(get("missing"), Some(Lit::Nil));
+        assert_eq!(get("number"), Some(Lit::Bool(true)));
+    }
+
+    #[test]
+    fn should_compare_maps_by_contents() {
+        let interpreter = interpret(r#"var same = {"a": 1, "b": 2} == {"b": 2, "a": 1};"#);
+
+        assert_eq!(
+            interpreter.environment.borrow().get("same"),
+            Some(Lit::Bool(true))
+        );
+    }
+
+    #[test]
+    fn should_print_and_compare_self_containing_values() {
+        let interpreter = interpret(
+            r#"
+            var xs = [1, 2];
+            xs[0] = xs;
+            var ys = [1, 2];
+            ys[0] = ys;
+            var zs = [1, 3];
+            zs[0] = zs;
+            var m = {"a": 1};
+            m["self"] = m;
+            var same = xs == ys;
+            var different = xs == zs;
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("xs").unwrap().to_string(), "[[...], 2]");
+        assert_eq!(get("m").unwrap().to_string(), r#"{"a": 1, "self": {...}}"#);
+        assert_eq!(get("same"), Some(Lit::Bool(true)));
+        assert_eq!(get("different"), Some(Lit::Bool(false)));
+        assert_eq!(get("xs"), get("xs"));
+    }
+
+    #[test]
+    fn should_error_on_unhashable_key() {
+        let result = try_interpret("var m = {}; m[[1]] = 1;");
+
+        assert!(result.is_err_and(|error| error.contains("Map keys must be")));
+    }
+
+    #[test]
+    fn should_interpolate_any_value() {
+        let interpreter = interpret(
+            r#"
+            var name = "wrig";
+            var count = 2;
+            var message = "Hello ${name}, you have ${count + 1} items in ${[1, "a"]} ${"nested ${count}"}";
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("message"),
+            Some(Lit::String(
+                r#"Hello wrig, you have 3 items in [1, "a"] nested 2"#.into()
+            ))
+        );
+    }
+
+    #[test]
+    fn should_keep_integer_arithmetic_exact() {
+        let interpreter = interpret(
+            r#"
+            var big = 9007199254740993 + 2;
+            var exact = 6 / 3;
+            var inexact = 7 / 2;
+            var mixed = 1 + 0.5;
+            "#,
+        );
+
+        let get = |name: &str| format!("{:?}", interpreter.environment.borrow().get(name));
+
+        assert_eq!(get("big"), "Some(Integer(9007199254740995))");
+        assert_eq!(get("exact"), "Some(Integer(2))");
+        assert_eq!(get("inexact"), "Some(Number(3.5))");
+        assert_eq!(get("mixed"), "Some(Number(1.5))");
+    }
+
+    #[test]
+    fn should_compare_integers_and_floats_by_value() {
+        let interpreter = interpret(
+            r#"
+            var equal = 1 == 1.0;
+            var precise = 9007199254740993 > 9007199254740992.0;
+            var m = {1: "one"};
+            var found = m[1.0];
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name);
+
+        assert_eq!(get("equal"), Some(Lit::Bool(true)));
+        assert_eq!(get("precise"), Some(Lit::Bool(true)));
+        assert_eq!(get("found"), Some(Lit::String("one".into())));
+    }
+
+    #[test]
+    fn should_promote_integers_instead_of_overflowing() {
+        let interpreter = interpret(
+            r#"
+            var max = 9223372036854775807;
+            var promoted = max + 1;
+            var product = max * max;
+            var demoted = promoted - 1;
+            var negated = -(-max - 1);
+            var halved = product / max;
+            var precise = promoted > 9223372036854775807.0;
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("promoted").to_string(), "9223372036854775808");
+        assert_eq!(
+            get("product").to_string(),
+            "85070591730234615847396907784232501249"
+        );
+        assert!(matches!(get("demoted"), Lit::Integer(i64::MAX)));
+        assert_eq!(get("negated").to_string(), "9223372036854775808");
+        assert!(matches!(get("halved"), Lit::Integer(i64::MAX)));
+        assert_eq!(get("precise"), Lit::Bool(false));
+    }
+
+    #[test]
+    fn should_raise_trivial_bases_to_huge_powers() {
+        let interpreter = interpret(
+            r#"
+            var huge = 2 ** 100;
+            var zero = 0 ** huge;
+            var one = 1 ** huge;
+            var even = (-1) ** huge;
+            var odd = (-1) ** (huge + 1);
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert!(matches!(get("zero"), Lit::Integer(0)));
+        assert!(matches!(get("one"), Lit::Integer(1)));
+        assert!(matches!(get("even"), Lit::Integer(1)));
+        assert!(matches!(get("odd"), Lit::Integer(-1)));
+
+        let too_large = try_interpret("var x = 2 ** (2 ** 100);");
+
+        assert!(too_large.is_err_and(|error| error.contains("Integer result is too large.")));
+    }
+
+    #[test]
+    fn should_error_on_integer_division_by_zero() {
+        let small = try_interpret("var x = 1 / 0;");
+        let big = try_interpret("var x = 99999999999999999999 / 0;");
+
+        assert!(
+            small.is_err_and(|error| error.contains("Integer division by zero. \nAt '/', line 1"))
+        );
+        assert!(big.is_err_and(|error| error.contains("division by zero")));
+    }
+
+    #[test]
+    fn should_evaluate_modulo_power_and_bitwise_operators() {
+        let interpreter = interpret(
+            r#"
+            var modulo = [7 % 3, -7 % 3, 7 % -3, -7.5 % 2];
+            var power = [2 ** 10, -2 ** 2, 2 ** 3 ** 2, 2 ** -1, 4 ** 0.5];
+            var bitwise = [6 & 3, 6 | 3, 6 ^ 3, ~5, 1 << 4, -16 >> 2, 8.0 & 12];
+            var big = 2 ** 200;
+            var wrapped = (2 ** 64 + 5) % 2 ** 64;
+            "#,
+        );
+
+        let get = |name: &str| {
+            interpreter
+                .environment
+                .borrow()
+                .get(name)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(get("modulo"), "[1, 2, -2, 0.5]");
+        assert_eq!(get("power"), "[1024, -4, 512, 0.5, 2]");
+        assert_eq!(get("bitwise"), "[2, 7, 5, -6, 16, -4, 8]");
+        assert_eq!(
+            get("big"),
+            "1606938044258990275541962092341162602522202993782792835301376"
+        );
+        assert_eq!(get("wrapped"), "5");
+    }
+
+    #[test]
+    fn should_reject_non_integral_bitwise_operands() {
+        let fraction = try_interpret("var x = 1.5 & 1;");
+        let not = try_interpret("var x = ~0.5;");
+        let shift = try_interpret("var x = 1 << -1;");
+        let modulo = try_interpret("var x = 1 % 0;");
+
+        assert!(fraction.is_err_and(|error| error.contains("need integer operands")));
+        assert!(not.is_err_and(|error| error.contains("need integer operands")));
+        assert!(shift.is_err_and(|error| error.contains("must not be negative")));
+        assert!(modulo.is_err_and(|error| error.contains("modulo by zero")));
+    }
+
+    #[test]
+    fn should_apply_compound_assignment_and_increments() {
+        let interpreter = interpret(
+            r#"
+            class Counter { init() { this.count = 0; } }
+            var x = 10;
+            x += 5;
+            x -= 3;
+            x *= 2;
+            x /= 4;
+            x %= 4;
+            var s = "a";
+            s += "b";
+            var counter = Counter();
+            var post = counter.count++;
+            var pre = ++counter.count;
+            var m = {"k": 1};
+            m["k"] *= 10;
+            var i = 0;
+            while (i < 3) i++;
+            "#,
+        );
+
+        let get = |name: &str| {
+            interpreter
+                .environment
+                .borrow()
+                .get(name)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(get("x"), "2");
+        assert_eq!(get("s"), "ab");
+        assert_eq!(get("post"), "0");
+        assert_eq!(get("pre"), "2");
+        assert_eq!(get("m"), r#"{"k": 10}"#);
+        assert_eq!(get("i"), "3");
+    }
+
+    #[test]
+    fn should_evaluate_update_target_once() {
+        let interpreter = interpret(
+            r#"
+            var calls = 0;
+            var xs = [1, 2, 3];
+            fun next() { calls += 1; return calls - 1; }
+            xs[next()] += 10;
+            xs[next()]--;
+            "#,
+        );
+
+        let get = |name: &str| {
+            interpreter
+                .environment
+                .borrow()
+                .get(name)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(get("calls"), "2");
+        assert_eq!(get("xs"), "[11, 1, 3]");
+    }
+
+    #[test]
+    fn should_error_on_undefined_update_target() {
+        let result = try_interpret("missing += 1;");
+
+        assert!(result.is_err_and(|error| error.contains("not been defined")));
+    }
+
+    #[test]
+    fn should_evaluate_conditional_lazily() {
+        let interpreter = interpret(
+            r#"
+            var calls = 0;
+            fun touch() { calls++; return calls; }
+            var big = 5 > 3 ? "yes" : touch();
+            var nested = false ? 1 : nil ? 2 : 3;
+            "#,
+        );
+
+        let get = |name: &str| {
+            interpreter
+                .environment
+                .borrow()
+                .get(name)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(get("big"), "yes");
+        assert_eq!(get("nested"), "3");
+        assert_eq!(get("calls"), "0");
+    }
+
+    #[test]
+    fn should_coalesce_only_nil() {
+        let interpreter = interpret(
+            r#"
+            var calls = 0;
+            fun touch() { calls++; return "default"; }
+            var kept = 0 ?? touch();
+            var empty = "" ?? touch();
+            var replaced = nil ?? touch();
+            "#,
+        );
+
+        let get = |name: &str| {
+            interpreter
+                .environment
+                .borrow()
+                .get(name)
+                .unwrap()
+                .to_string()
+        };
+
+        assert_eq!(get("kept"), "0");
+        assert_eq!(get("empty"), "");
+        assert_eq!(get("replaced"), "default");
+        assert_eq!(get("calls"), "1");
+    }
+
+    #[test]
+    fn should_short_circuit_optional_chains() {
+        let interpreter = interpret(
+            r#"
+            class User { init(name) { this.name = name; } greet() { return "hi " + this.name; } }
+            var user = User("ada");
+            var nobody = nil;
+            var name = user?.name;
+            var greeting = user?.greet();
+            var missing = nobody?.name.first;
+            var called = nobody?.greet();
+            var indexed = nobody?.tags[0];
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("name"), Lit::String("ada".into()));
+        assert_eq!(get("greeting"), Lit::String("hi ada".into()));
+        assert_eq!(get("missing"), Lit::Nil);
+        assert_eq!(get("called"), Lit::Nil);
+        assert_eq!(get("indexed"), Lit::Nil);
+    }
+
+    #[test]
+    fn should_only_short_circuit_within_the_chain() {
+        let result = try_interpret("var nobody = nil; (nobody?.name).first;");
+
+        assert!(result.is_err_and(|error| error.contains("Only instances have properties")));
+    }
+
+    #[test]
+    fn should_catch_thrown_values_and_run_finally() {
+        let interpreter = interpret(
+            r#"
+            var log = "";
+            try {
+                log += "try ";
+                throw "boom";
+                log += "unreachable ";
+            } catch (e) {
+                log += "caught " + e + " ";
+            } finally {
+                log += "finally";
+            }
+            "#,
+        );
+
+        assert_eq!(
+            interpreter.environment.borrow().get("log"),
+            Some(Lit::String("try caught boom finally".into()))
+        );
+    }
+
+    #[test]
+    fn should_catch_runtime_errors_as_values() {
+        let interpreter = interpret(
+            r#"
+            var message;
+            var line;
+            try {
+                var x = 1;
+
+                x = x / 0;
+            } catch (error) {
+                message = error.message;
+                line = error.line;
+            }
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(
+            get("message"),
+            Lit::String("Integer division by zero.".into())
+        );
+        assert_eq!(get("line"), Lit::Integer(7));
+    }
+
+    #[test]
+    fn should_run_finally_on_return_and_unwind_through_calls() {
+        let interpreter = interpret(
+            r#"
+            var cleaned = 0;
+            fun work() {
+                try { return "done"; } finally { cleaned++; }
+            }
+            fun fail() { missing; }
+            var result = work();
+            var caught = nil;
+            try { fail(); } catch (e) { caught = e.message; } finally { cleaned++; }
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("result"), Lit::String("done".into()));
+        assert_eq!(get("cleaned"), Lit::Integer(2));
+        assert_eq!(
+            get("caught"),
+            Lit::String("A variable has been used that has not been defined.".into())
+        );
+    }
+
+    #[test]
+    fn should_report_uncaught_errors_with_location() {
+        let thrown = try_interpret("\nthrow \"boom\";");
+        let rethrown = try_interpret("try { nil + 1; } catch (e) { throw e; }");
+        let through_finally = try_interpret("try { throw 1; } finally { print 2; }");
+
+        assert!(thrown.is_err_and(|error| error == "Uncaught boom. \nAt 'throw', line 2."));
+        assert!(rethrown.is_err_and(|error| error.contains("At '+', line 1")));
+        assert!(through_finally.is_err_and(|error| error.contains("Uncaught 1")));
+    }
+
+    #[test]
+    fn should_evaluate_first_matching_arm() {
+        let interpreter = interpret(
+            r#"
+            fun describe(value) {
+                return match value {
+                    0 => "zero",
+                    1..10 => "small",
+                    10..=20 => "teen",
+                    [] => "empty",
+                    [only] => "one ${only}",
+                    [first, ..rest] => "${first} then ${rest}",
+                    "hi" => "greeting",
+                    n if n > 100 and n < 1000 => "big ${n}",
+                    _ => "other",
+                };
+            }
+            var results = [
+                describe(0), describe(9), describe(10), describe(20), describe([]),
+                describe([1]), describe([1, 2, 3]), describe(500), describe("hi"), describe(5000)
+            ];
+            "#,
+        );
+
+        assert_eq!(
+            interpreter
+                .environment
+                .borrow()
+                .get("results")
+                .unwrap()
+                .to_string(),
+            r#"["zero", "small", "teen", "teen", "empty", "one 1", "1 then [2, 3]", "big 500", "greeting", "other"]"#
+        );
+    }
+
+    #[test]
+    fn should_scope_match_bindings_to_their_arm() {
+        let interpreter = interpret(
+            r#"
+            var first = "outer";
+            var inner = match [1, 2] { [first, second] if first > second => "no", [first, _] => first };
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("inner"), Lit::Integer(1));
+        assert_eq!(get("first"), Lit::String("outer".into()));
+    }
+
+    #[test]
+    fn should_error_when_no_arm_matches() {
+        let result = try_interpret("\nvar x = match 5 { 1 => 2, n if n < 0 => 3 };");
+
+        assert!(result.is_err_and(|error| error == "No match arm matches 5. \nAt 'match', line 2."));
+    }
+
+    #[test]
+    fn should_construct_print_and_compare_enum_values() {
+        let interpreter = interpret(
+            r#"
+            enum Shape { Circle(r), Rect(w, h), Empty }
+            var circle = Shape.Circle(2);
+            var printed = "${circle} ${Shape.Rect(1, "a")} ${Shape.Empty}";
+            var radius = circle.r;
+            var same = circle == Shape.Circle(2) and Shape.Empty == Shape.Empty;
+            var different = circle == Shape.Circle(3) or circle == Shape.Rect(2, 2);
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(
+            get("printed"),
+            Lit::String(r#"Shape.Circle(2) Shape.Rect(1, "a") Shape.Empty"#.into())
+        );
+        assert_eq!(get("radius"), Lit::Integer(2));
+        assert_eq!(get("same"), Lit::Bool(true));
+        assert_eq!(get("different"), Lit::Bool(false));
+    }
+
+    #[test]
+    fn should_match_enum_variants() {
+        let interpreter = interpret(
+            r#"
+            enum State { Idle, Running(job), Failed(reason, retries) }
+            fun next(state) {
+                return match state {
+                    State.Idle => State.Running("build"),
+                    State.Running(job) => State.Failed("${job} broke", 0),
+                    State.Failed(_, retries) if retries < 2 => State.Failed("again", retries + 1),
+                    State.Failed => State.Idle,
+                };
+            }
+            var history = [nil, nil, nil, nil, nil, nil];
+            var state = State.Idle;
+            for (var i = 0; i < 6; i++) {
+                history[i] = state;
+                state = next(state);
+            }
+            "#,
+        );
+
+        assert_eq!(
+            interpreter
+                .environment
+                .borrow()
+                .get("history")
+                .unwrap()
+                .to_string(),
+            r#"[State.Idle, State.Running("build"), State.Failed("build broke", 0), State.Failed("again", 1), State.Failed("again", 2), State.Idle]"#
+        );
+    }
+
+    #[test]
+    fn should_error_on_invalid_enum_use() {
+        let cases = [
+            (
+                "enum E { A(x) } E.A(1, 2);",
+                "Got 2 arguments. Expected E.A(x).",
+            ),
+            ("enum E { A } E.B;", "Undefined variant"),
+            ("enum E { A(x) } E.A(1).y;", "Undefined field"),
+            (
+                "enum E { A(x) } match E.A(1) { E.A(x, y) => 1 };",
+                "E.A has 1 fields but the pattern has 2",
+            ),
+            ("enum E { A } match E.A { E.C => 1 };", "Undefined variant"),
+        ];
+
+        for (source, expected) in cases {
+            let result = try_interpret(source);
+
+            assert!(
+                result.is_err_and(|error| error.contains(expected)),
+                "{source}"
+            );
         }
+    }
 
-        Ok(())
+    #[test]
+    fn should_destructure_declarations_and_swap() {
+        let interpreter = interpret(
+            r#"
+            class Person { init(name, age) { this.name = name; this.age = age; } }
+            var [first, second, ..rest] = [1, 2, 3, 4];
+            var [only, ..] = ["x", "y"];
+            var {name, age: years} = Person("Ada", 36);
+            var {city} = {"city": "Leeds", "country": "UK"};
+            var [[p, q], {z}] = [[5, 6], {"z": 7}];
+            [first, second] = [second, first];
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("first"), Lit::Integer(2));
+        assert_eq!(get("second"), Lit::Integer(1));
+        assert_eq!(get("rest").to_string(), "[3, 4]");
+        assert_eq!(get("only"), Lit::String("x".into()));
+        assert_eq!(get("name"), Lit::String("Ada".into()));
+        assert_eq!(get("years"), Lit::Integer(36));
+        assert_eq!(get("city"), Lit::String("Leeds".into()));
+        assert_eq!(get("p"), Lit::Integer(5));
+        assert_eq!(get("q"), Lit::Integer(6));
+        assert_eq!(get("z"), Lit::Integer(7));
+    }
+
+    #[test]
+    fn should_match_record_patterns() {
+        let interpreter = interpret(
+            r#"
+            fun greet(user) {
+                return match user {
+                    {name, admin: true} => "Welcome back, ${name}",
+                    {name} => "Hello, ${name}",
+                    _ => "Who are you?",
+                };
+            }
+            var greetings = [greet({"name": "Ada", "admin": true}), greet({"name": "Bob"}), greet(5)];
+            "#,
+        );
+
+        assert_eq!(
+            interpreter
+                .environment
+                .borrow()
+                .get("greetings")
+                .unwrap()
+                .to_string(),
+            r#"["Welcome back, Ada", "Hello, Bob", "Who are you?"]"#
+        );
+    }
+
+    #[test]
+    fn should_name_the_position_of_a_destructuring_mismatch() {
+        let cases = [
+            (
+                "var [a, b] = [1];",
+                "Cannot destructure [1] into [a, b]: expected 2 elements. \nAt '=', line 1.",
+            ),
+            (
+                "var [a, [b, ..c]] = [1, []];",
+                "Cannot destructure [] into [b, ..c] at position [1]: expected at least 1 elements.",
+            ),
+            (
+                "var {user: {name}} = {\"user\": {}};",
+                "Cannot destructure {} into {name} at position .user: missing name.",
+            ),
+            (
+                "var [a, {b}] = [1, 2];",
+                "Cannot destructure 2 into {b} at position [1]: expected a map or an instance.",
+            ),
+            ("[a, b] = [1, 2];", "Cannot assign to an undefined variable a."),
+        ];
+
+        for (source, expected) in cases {
+            let result = try_interpret(source);
+
+            assert!(
+                result.is_err_and(|error| error.starts_with(expected)),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_leave_targets_unchanged_when_destructuring_assignment_fails() {
+        let interpreter = interpret("var a = 1; try { [a, b] = [2, 3]; } catch (e) {}");
+
+        assert_eq!(
+            interpreter.environment.borrow().get("a"),
+            Some(Lit::Number(1.0))
+        );
+    }
+
+    #[test]
+    fn should_call_anonymous_and_arrow_functions() {
+        let interpreter = interpret(
+            r#"
+            fun map(list, f) {
+                var result = [nil, nil, nil];
+                for (var i = 0; i < 3; i++) result[i] = f(list[i]);
+                return result;
+            }
+            var doubled = map([1, 2, 3], (x) => x * 2);
+            var described = map([1, 2, 3], fun (x) {
+                if (x == 2) return "two";
+                return x;
+            });
+            var add = (a) => (b) => a + b;
+            var sum = add(3)(4);
+            var answer = (() => 42)();
+            var grouped = (1 + 2) * 3;
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("doubled").to_string(), "[2, 4, 6]");
+        assert_eq!(get("described").to_string(), r#"[1, "two", 3]"#);
+        assert_eq!(get("sum"), Lit::Integer(7));
+        assert_eq!(get("answer"), Lit::Integer(42));
+        assert_eq!(get("grouped"), Lit::Integer(9));
+    }
+
+    #[test]
+    fn should_capture_scope_in_lambdas() {
+        let interpreter = interpret(
+            r#"
+            fun counter() {
+                var count = 0;
+                return () => ++count;
+            }
+            class Box {
+                init(value) { this.value = value; }
+                reader() { return fun () { return this.value; }; }
+            }
+            var next = counter();
+            next();
+            var count = next();
+            var boxed = Box("kept").reader()();
+            fun (x) { boxed = boxed + x; }("!");
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("count"), Lit::Integer(2));
+        assert_eq!(get("boxed"), Lit::String("kept!".into()));
+    }
+
+    #[test]
+    fn should_fill_defaults_named_and_rest_parameters() {
+        let interpreter = interpret(
+            r#"
+            fun greet(name, greeting = "hi", punctuation = greeting == "hi" ? "." : "!") {
+                return greeting + " " + name + punctuation;
+            }
+            fun sum(..numbers) {
+                return match numbers { [] => 0, [first, ..rest] => first + sum(..rest) };
+            }
+            fun count(first, ..rest) { return rest; }
+            var plain = greet("Ada");
+            var named = greet(greeting: "yo", name: "Bob");
+            var mixed = greet("Cy", punctuation: "?");
+            var numbers = [2, 3, 4];
+            var spread = sum(1, ..numbers, ..[10]);
+            var none = count(1);
+            var arrow = ((x, y = 2) => x * y)(5);
+            enum Shape { Rect(w, h) }
+            var rect = Shape.Rect(h: 2, w: 1);
+            "#,
+        );
+
+        let get = |name: &str| interpreter.environment.borrow().get(name).unwrap();
+
+        assert_eq!(get("plain"), Lit::String("hi Ada.".into()));
+        assert_eq!(get("named"), Lit::String("yo Bob!".into()));
+        assert_eq!(get("mixed"), Lit::String("hi Cy?".into()));
+        assert_eq!(get("spread"), Lit::Integer(20));
+        assert_eq!(get("none").to_string(), "[]");
+        assert_eq!(get("arrow"), Lit::Integer(10));
+        assert_eq!(get("rect").to_string(), "Shape.Rect(1, 2)");
+    }
+
+    #[test]
+    fn should_show_signature_on_argument_errors() {
+        let cases = [
+            (
+                r#"fun greet(name, greeting = "hi") {} greet(1, 2, 3);"#,
+                r#"Got 3 arguments. Expected greet(name, greeting = "hi"). "#,
+            ),
+            (
+                "fun f(a, ..rest) {} f(b: 1);",
+                "Unexpected argument b. Expected f(a, ..rest).",
+            ),
+            (
+                "fun f(a) {} f(1, a: 2);",
+                "Argument a was given twice. Expected f(a).",
+            ),
+            (
+                "class Point { init(x, y = 0) {} } Point();",
+                "Missing argument x. Expected Point(x, y = 0).",
+            ),
+            (
+                "fun f(a = 1 + 2, b = [1], c = -1) {} f(1, 2, 3, 4);",
+                "Got 4 arguments. Expected f(a = …, b = …, c = …).",
+            ),
+            (
+                "class Empty {} Empty(1);",
+                "Got 1 argument. Expected Empty().",
+            ),
+            ("fun f(a) {} f(..5);", "Only lists can be spread, not 5."),
+        ];
+
+        for (source, expected) in cases {
+            let result = try_interpret(source);
+
+            assert!(
+                result.is_err_and(|error| error.starts_with(expected)),
+                "{source}"
+            );
+        }
     }
 }
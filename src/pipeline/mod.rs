@@ -1,4 +1,4 @@
-mod environment;
+pub mod environment;
 mod evaluator;
 mod interpreter;
 mod parser;
@@ -1,15 +1,47 @@
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
 use thiserror::Error;
 
 use crate::{
     error::{CodeLocation, DbgDisplay},
-    types::{Expr, Lit, Stmt, Tok, TokType},
+    types::{negate, Argument, Expr, Lit, MatchArm, Param, Pattern, Stmt, Tok, TokType},
 };
 
+/// How deeply statements and expressions may nest. Parsing and evaluating recurse once per level, so this keeps
+/// both from running out of native stack on something like thousands of nested parentheses.
+pub const MAX_NESTING: usize = 500;
+
 // I know there is so much repetition in this file and unoptimised code 🤣 but it'll do for my first prototype
 
 pub struct Parser<'a> {
     tokens: &'a Vec<Tok>,
     current: usize,
+    /// How many loops enclose the statement being parsed. Used to reject stray `break` and `continue`.
+    loop_depth: usize,
+    /// How many functions enclose the statement being parsed. Used to reject a stray `return`.
+    function_depth: usize,
+    /// How many classes enclose the expression being parsed. Used to reject a stray `this`.
+    class_depth: usize,
+    /// Whether the innermost function is a class initializer, which cannot return a value.
+    in_initializer: bool,
+    /// Whether the innermost class has a superclass. Used to reject a stray `super`.
+    in_subclass: bool,
+    /// Problems that do not stop the program from running, reported alongside a successful parse.
+    warnings: Vec<ParserWarning>,
+    /// The variant names of each enum declared so far. Used to warn about matches that miss a variant.
+    enums: HashMap<String, Vec<String>>,
+    /// Where the match guard being parsed starts, if any. A parenthesised guard like `if (x > 1) => ...`
+    /// is followed by the arm's `=>`, so it must not be taken for an arrow function.
+    guard_start: Option<usize>,
+    /// The names declared so far in each local scope around the code being parsed, innermost last, matching the
+    /// scopes the interpreter creates. Variables record how many scopes out they were found. The global scope
+    /// is left out, so a name found nowhere is a global.
+    scopes: Vec<HashSet<String>>,
+    /// How many levels of statements and expressions enclose the code being parsed. Bounded by `MAX_NESTING`.
+    nesting: usize,
 }
 
 #[derive(Error, Debug)]
@@ -22,11 +54,68 @@ pub enum ParserError {
     PrimaryError(CodeLocation),
     #[error("No literal type found: {0}")]
     UndefinedLiteral(CodeLocation),
+    #[error("Invalid assignment target. {0}")]
+    InvalidAssignmentTarget(CodeLocation),
+    #[error("Loop control used outside of a loop. {0}")]
+    LoopControlOutsideLoop(CodeLocation),
+    #[error("Return used outside of a function. {0}")]
+    ReturnOutsideFunction(CodeLocation),
+    #[error("Cannot return a value from an initializer. {0}")]
+    ReturnValueFromInitializer(CodeLocation),
+    #[error("This used outside of a class. {0}")]
+    ThisOutsideClass(CodeLocation),
+    #[error("A class cannot inherit from itself. {0}")]
+    SelfInheritance(CodeLocation),
+    #[error("Super used outside of a class with a superclass. {0}")]
+    SuperOutsideSubclass(CodeLocation),
+    #[error("Try must be followed by catch or finally. {0}")]
+    TryWithoutHandler(CodeLocation),
+    #[error("Invalid pattern. {0}")]
+    InvalidPattern(CodeLocation),
+    #[error("A variant with this name is already declared in the enum. {0}")]
+    DuplicateVariant(CodeLocation),
+    #[error("A field with this name is already declared in the variant. {0}")]
+    DuplicateField(CodeLocation),
+    #[error("A parameter with this name is already declared. {0}")]
+    DuplicateParameter(CodeLocation),
+    #[error("A name is bound more than once in the pattern. {0}")]
+    DuplicateBinding(CodeLocation),
+    #[error("A rest parameter must be the last parameter. {0}")]
+    RestParameterNotLast(CodeLocation),
+    #[error("A parameter without a default cannot follow one with a default. {0}")]
+    RequiredAfterDefault(CodeLocation),
+    #[error("Positional arguments must come before named arguments. {0}")]
+    PositionalAfterNamed(CodeLocation),
+    #[error("Code is nested too deeply. {0}")]
+    NestedTooDeeply(CodeLocation),
+}
+
+#[derive(Error, Debug)]
+pub enum ParserWarning {
+    #[error("Match does not handle {1}. {0}")]
+    NonExhaustiveMatch(CodeLocation, String),
 }
 
 impl<'a> Parser<'a> {
     pub fn new(tokens: &'a Vec<Tok>) -> Self {
-        Self { current: 0, tokens }
+        Self {
+            current: 0,
+            tokens,
+            loop_depth: 0,
+            function_depth: 0,
+            class_depth: 0,
+            in_initializer: false,
+            in_subclass: false,
+            warnings: vec![],
+            enums: HashMap::new(),
+            guard_start: None,
+            scopes: vec![],
+            nesting: 0,
+        }
+    }
+
+    pub fn warnings(&self) -> &[ParserWarning] {
+        &self.warnings
     }
 
     pub fn parse(&mut self) -> Result<Vec<Stmt>, ParserError> {
@@ -34,6 +123,9 @@ impl<'a> Parser<'a> {
         let mut errors: Vec<ParserError> = vec![];
 
         while !self.is_at_end() {
+            // An error leaves the levels it was found in uncounted, so start each top-level declaration afresh
+            self.nesting = 0;
+
             let statement = self.declaration();
 
             match statement {
@@ -42,7 +134,7 @@ impl<'a> Parser<'a> {
             }
         }
 
-        if errors.len() > 0 {
+        if !errors.is_empty() {
             let error = errors
                 .iter()
                 .map(|error| error.to_string())
@@ -75,13 +167,13 @@ impl<'a> Parser<'a> {
 
             Ok(self.previous())
         } else {
-            return Err(ParserError::UnexpectedToken(
+            Err(ParserError::UnexpectedToken(
                 CodeLocation {
                     line: token.line,
                     display: DbgDisplay::from(token),
                 },
                 token_type,
-            ));
+            ))
         }
     }
 
@@ -96,7 +188,7 @@ impl<'a> Parser<'a> {
             return false;
         }
 
-        return self.peek().token_type == token_type;
+        self.peek().token_type == token_type
     }
 
     // I could create a macro for variable length params, but this is cleaner and less confusing
@@ -113,6 +205,54 @@ impl<'a> Parser<'a> {
         false
     }
 
+    /// Whether the token after the current one has the given type.
+    fn check_next(&self, token_type: TokType) -> bool {
+        self.tokens
+            .get(self.current + 1)
+            .is_some_and(|token| token.token_type == token_type)
+    }
+
+    /// Whether the current `(` opens the parameters of an arrow function like `(x, y) => x + y`,
+    /// rather than a grouping. That is decided by whether `=>` follows the matching `)`.
+    fn is_arrow_function(&self) -> bool {
+        // Only a paren at the top level of a guard can be followed by the arm's `=>`
+        if let Some(start) = self.guard_start {
+            let nesting =
+                self.tokens[start..self.current]
+                    .iter()
+                    .fold(0, |depth, token| match token.token_type {
+                        TokType::LeftParen | TokType::LeftBracket | TokType::LeftBrace => depth + 1,
+                        TokType::RightParen | TokType::RightBracket | TokType::RightBrace => {
+                            depth - 1
+                        }
+                        _ => depth,
+                    });
+
+            if nesting == 0 {
+                return false;
+            }
+        }
+
+        let mut depth = 0;
+
+        for (position, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.token_type {
+                TokType::LeftParen => depth += 1,
+                TokType::RightParen if depth == 1 => {
+                    return self
+                        .tokens
+                        .get(position + 1)
+                        .is_some_and(|token| token.token_type == TokType::EqualGreater);
+                }
+                TokType::RightParen => depth -= 1,
+                TokType::Eof => return false,
+                _ => (),
+            }
+        }
+
+        false
+    }
+
     fn previous(&self) -> &Tok {
         self.tokens
             .get(self.current - 1)
@@ -126,7 +266,17 @@ impl<'a> Parser<'a> {
             return self.var_declaration();
         }
 
-        let statement = self.statement();
+        // `fun (` starts an anonymous function, which is an expression rather than a declaration
+        let statement = if self.match_token(TokType::Fun) && !self.check_next(TokType::LeftParen) {
+            self.advance();
+            self.function_declaration(false)
+        } else if self.match_tokens_then_advance(&[TokType::Class]) {
+            self.class_declaration()
+        } else if self.match_tokens_then_advance(&[TokType::Enum]) {
+            self.enum_declaration()
+        } else {
+            self.statement()
+        };
 
         if statement.is_err() {
             self.synchronise();
@@ -135,291 +285,2306 @@ impl<'a> Parser<'a> {
         statement
     }
 
-    fn var_declaration(&mut self) -> Result<Stmt, ParserError> {
-        let token = self.consume(TokType::Identifier)?.clone();
+    fn class_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokType::Identifier)?.clone();
 
-        let initializer = if self.match_token(TokType::Equal) {
-            self.advance();
-            self.expression()?
+        let superclass = if self.match_tokens_then_advance(&[TokType::Less]) {
+            let superclass = self.consume(TokType::Identifier)?.clone();
+
+            if superclass.lexeme == name.lexeme {
+                return Err(ParserError::SelfInheritance(CodeLocation {
+                    line: superclass.line,
+                    display: DbgDisplay::from(&superclass),
+                }));
+            }
+
+            Some(Expr::Variable {
+                depth: self.resolve(&superclass),
+                name: superclass,
+            })
         } else {
-            Expr::Literal { value: Lit::Nil }
+            None
         };
 
-        self.consume(TokType::Semicolon)?;
+        self.declare(&name);
+        self.consume(TokType::LeftBrace)?;
 
-        Ok(Stmt::Var {
-            name: token,
-            expr: initializer,
-        })
-    }
+        let enclosing_in_subclass = std::mem::replace(&mut self.in_subclass, superclass.is_some());
 
-    fn statement(&mut self) -> Result<Stmt, ParserError> {
-        return if self.match_tokens_then_advance(&[TokType::Print]) {
-            self.print_statement()
+        self.class_depth += 1;
+
+        // Methods close over a scope holding `super` if there is a superclass, and are bound to `this` in another
+        let methods = if superclass.is_some() {
+            self.scoped(|parser| {
+                parser.declare_name("super");
+                parser.scoped(Self::methods)
+            })
         } else {
-            self.expression_statement()
+            self.scoped(Self::methods)
         };
-    }
 
-    fn print_statement(&mut self) -> Result<Stmt, ParserError> {
-        let value = self.expression()?;
+        self.class_depth -= 1;
+        self.in_subclass = enclosing_in_subclass;
 
-        self.consume(TokType::Semicolon)?;
+        let methods = methods?;
 
-        Ok(Stmt::Print { expr: value })
+        self.consume(TokType::RightBrace)?;
+
+        Ok(Stmt::Class {
+            name,
+            superclass,
+            methods,
+        })
     }
 
-    fn expression_statement(&mut self) -> Result<Stmt, ParserError> {
-        let expr = self.expression()?;
+    /// Parses the methods of a class up to its closing brace, in the scope that holds `this`.
+    fn methods(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut methods: Vec<Stmt> = vec![];
 
-        self.consume(TokType::Semicolon)?;
+        self.declare_name("this");
 
-        Ok(Stmt::Expr { expr })
-    }
+        while !self.match_token(TokType::RightBrace) && !self.is_at_end() {
+            methods.push(self.function_declaration(true)?);
+        }
 
-    fn expression(&mut self) -> Result<Expr, ParserError> {
-        self.equality()
+        Ok(methods)
     }
 
-    fn equality(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.comparison()?;
-        let token_types = [TokType::BangEqual, TokType::EqualEqual];
+    fn enum_declaration(&mut self) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokType::Identifier)?.clone();
 
-        while self.match_tokens_then_advance(&token_types) {
-            let operator = self.previous().clone();
-            let right = self.comparison()?;
+        self.declare(&name);
 
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
+        let mut variants: Vec<(Tok, Vec<Tok>)> = vec![];
+
+        self.consume(TokType::LeftBrace)?;
+
+        while !self.match_token(TokType::RightBrace) {
+            let variant = self.consume(TokType::Identifier)?.clone();
+            let mut fields: Vec<Tok> = vec![];
+
+            if variants
+                .iter()
+                .any(|(existing, _)| existing.lexeme == variant.lexeme)
+            {
+                return Err(ParserError::DuplicateVariant(CodeLocation {
+                    line: variant.line,
+                    display: DbgDisplay::from(&variant),
+                }));
             }
-        }
 
-        Ok(expr)
-    }
+            if self.match_tokens_then_advance(&[TokType::LeftParen]) {
+                loop {
+                    let field = self.consume(TokType::Identifier)?.clone();
+
+                    if fields
+                        .iter()
+                        .any(|existing| existing.lexeme == field.lexeme)
+                    {
+                        return Err(ParserError::DuplicateField(CodeLocation {
+                            line: field.line,
+                            display: DbgDisplay::from(&field),
+                        }));
+                    }
+
+                    fields.push(field);
+
+                    if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                        break;
+                    }
+                }
 
-    fn comparison(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.term()?;
-        let token_types = [
-            TokType::Greater,
-            TokType::GreaterEqual,
-            TokType::Less,
-            TokType::LessEqual,
-        ];
+                self.consume(TokType::RightParen)?;
+            }
 
-        while self.match_tokens_then_advance(&token_types) {
-            let operator = self.previous().clone();
-            let right = self.term()?;
+            variants.push((variant, fields));
 
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
+            if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                break;
             }
         }
 
-        Ok(expr)
-    }
+        self.consume(TokType::RightBrace)?;
 
-    fn term(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.unary()?;
-        let token_types = [TokType::Minus, TokType::Plus, TokType::Star, TokType::Slash];
+        self.enums.insert(
+            name.lexeme.clone(),
+            variants
+                .iter()
+                .map(|(variant, _)| variant.lexeme.clone())
+                .collect(),
+        );
 
-        while self.match_tokens_then_advance(&token_types) {
-            let operator = self.previous().clone();
-            let right = self.factor()?;
+        Ok(Stmt::Enum { name, variants })
+    }
 
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
-            }
+    /// Methods are declared like functions, just without the `fun` keyword.
+    fn function_declaration(&mut self, is_method: bool) -> Result<Stmt, ParserError> {
+        let name = self.consume(TokType::Identifier)?.clone();
+
+        // Declared before the body so that the function can call itself
+        if !is_method {
+            self.declare(&name);
         }
 
-        Ok(expr)
+        self.consume(TokType::LeftParen)?;
+
+        let (params, body) = self.function_scope(is_method && name.lexeme == "init", |parser| {
+            parser.consume(TokType::LeftBrace)?;
+            parser.block()
+        })?;
+
+        Ok(Stmt::Function {
+            name,
+            params,
+            body: Rc::new(body),
+        })
     }
 
-    fn factor(&mut self) -> Result<Expr, ParserError> {
-        let mut expr = self.unary()?;
-        let token_types = [TokType::Minus, TokType::Plus];
+    /// Parses a parameter list up to and including the closing paren. Assumes the opening paren has been consumed.
+    /// Parameters with defaults come after the ones without, and the rest parameter comes last.
+    fn parameters(&mut self) -> Result<Rc<Vec<Param>>, ParserError> {
+        let mut params: Vec<Param> = vec![];
+
+        while !self.match_token(TokType::RightParen) {
+            if params.last().is_some_and(|param| param.rest) {
+                return Err(ParserError::RestParameterNotLast(CodeLocation {
+                    line: self.peek().line,
+                    display: DbgDisplay::from(self.peek()),
+                }));
+            }
 
-        while self.match_tokens_then_advance(&token_types) {
-            let operator = self.previous().clone();
-            let right = self.unary()?;
+            let rest = self.match_tokens_then_advance(&[TokType::DotDot]);
+            let name = self.consume(TokType::Identifier)?.clone();
 
-            expr = Expr::Binary {
-                left: Box::new(expr),
-                operator,
-                right: Box::new(right),
+            if params.iter().any(|param| param.name.lexeme == name.lexeme) {
+                return Err(ParserError::DuplicateParameter(CodeLocation {
+                    line: name.line,
+                    display: DbgDisplay::from(&name),
+                }));
+            }
+
+            let default = if !rest && self.match_tokens_then_advance(&[TokType::Equal]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+
+            let follows_default = params.last().is_some_and(|param| param.default.is_some());
+
+            if !rest && default.is_none() && follows_default {
+                return Err(ParserError::RequiredAfterDefault(CodeLocation {
+                    line: name.line,
+                    display: DbgDisplay::from(&name),
+                }));
+            }
+
+            // Declared after its default, which can only see the parameters before it
+            self.declare(&name);
+
+            params.push(Param {
+                name,
+                default,
+                rest,
+            });
+
+            if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                break;
             }
         }
 
-        Ok(expr)
+        self.consume(TokType::RightParen)?;
+
+        Ok(Rc::new(params))
     }
 
-    fn unary(&mut self) -> Result<Expr, ParserError> {
-        let token_types = [TokType::Bang, TokType::Minus];
+    /// Parses a parameter list and then a function body with `parse`, tracking that it is inside a function.
+    /// Assumes the opening paren has been consumed. Like a call, the parameters get a scope that encloses the body's.
+    fn function_scope<T>(
+        &mut self,
+        is_initializer: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<(Rc<Vec<Param>>, T), ParserError> {
+        self.scoped(|parser| {
+            let params = parser.parameters()?;
 
-        if self.match_tokens_then_advance(&token_types) {
-            let operator = self.previous().clone();
-            let right = self.unary()?;
+            // Loops outside the function cannot be broken out of from inside it
+            let enclosing_loop_depth = std::mem::replace(&mut parser.loop_depth, 0);
+            let enclosing_in_initializer =
+                std::mem::replace(&mut parser.in_initializer, is_initializer);
 
-            let unary = Expr::Unary {
-                operator,
-                right: Box::new(right),
-            };
+            parser.function_depth += 1;
 
-            return Ok(unary);
-        }
+            let body = parser.scoped(parse);
 
-        self.primary()
+            parser.function_depth -= 1;
+            parser.loop_depth = enclosing_loop_depth;
+            parser.in_initializer = enclosing_in_initializer;
+
+            Ok((params, body?))
+        })
     }
 
-    fn primary(&mut self) -> Result<Expr, ParserError> {
-        if self.match_tokens_then_advance(&[TokType::Identifier]) {
-            return Ok(Expr::Variable {
-                name: self.previous().clone(),
-            });
-        }
+    /// Parses with `parse` in a new local scope, for code the interpreter runs in a scope of its own.
+    fn scoped<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        self.scopes.push(HashSet::new());
 
-        if self.match_tokens_then_advance(&[TokType::True]) {
-            return Ok(Expr::Literal {
-                value: Lit::Bool(true),
-            });
-        }
+        let result = parse(self);
 
-        if self.match_tokens_then_advance(&[TokType::False]) {
-            return Ok(Expr::Literal {
-                value: Lit::Bool(false),
-            });
-        }
+        self.scopes.pop();
 
-        if self.match_tokens_then_advance(&[TokType::Nil]) {
-            return Ok(Expr::Literal { value: Lit::Nil });
-        }
+        result
+    }
 
-        if self.match_tokens_then_advance(&[TokType::Number, TokType::String]) {
-            return Ok(Expr::Literal {
-                value: self
-                    .previous()
-                    .literal
-                    .clone()
-                    .ok_or(ParserError::UndefinedLiteral(CodeLocation {
-                        line: self.peek().line,
-                        display: DbgDisplay::from(self.peek()),
-                    }))?,
-            });
-        }
+    /// Parses with `parse` one level deeper, restoring the level afterwards.
+    fn nested<T>(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError>,
+    ) -> Result<T, ParserError> {
+        self.deepen()?;
 
-        if self.match_tokens_then_advance(&[TokType::LeftParen]) {
-            let expr = self.expression()?;
+        let result = parse(self);
 
-            self.consume(TokType::RightParen)?;
+        self.nesting -= 1;
 
-            return Ok(Expr::Grouping {
-                expression: Box::new(expr),
-            });
+        result
+    }
+
+    /// Goes one level deeper, failing at the current token once `MAX_NESTING` is reached.
+    fn deepen(&mut self) -> Result<(), ParserError> {
+        if self.nesting == MAX_NESTING {
+            return Err(ParserError::NestedTooDeeply(CodeLocation {
+                line: self.peek().line,
+                display: DbgDisplay::from(self.peek()),
+            }));
         }
 
-        Err(ParserError::PrimaryError(CodeLocation {
-            line: self.peek().line,
-            display: DbgDisplay::from(self.peek()),
-        }))
+        self.nesting += 1;
+
+        Ok(())
     }
 
-    fn synchronise(&mut self) {
-        self.advance();
+    /// Declares a variable in the innermost local scope. Globals are not tracked.
+    fn declare(&mut self, name: &Tok) {
+        self.declare_name(&name.lexeme);
+    }
 
-        while !self.is_at_end() {
-            if self.previous().token_type == TokType::Semicolon {
-                return;
-            }
+    fn declare_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string());
+        }
+    }
 
-            match self.peek().token_type {
-                TokType::Class
-                | TokType::Fun
-                | TokType::Var
-                | TokType::For
-                | TokType::If
-                | TokType::While
-                | TokType::Print
-                | TokType::Return => return,
-                _ => {
-                    self.advance();
-                    return;
+    /// Declares the functions of the block about to be parsed up front, so that sibling functions can call
+    /// each other regardless of their order. Assumes the opening brace has been consumed.
+    fn declare_local_functions(&mut self) {
+        let mut depth = 0;
+        let mut names: Vec<String> = vec![];
+
+        for (position, token) in self.tokens.iter().enumerate().skip(self.current) {
+            match token.token_type {
+                TokType::LeftParen | TokType::LeftBracket | TokType::LeftBrace => depth += 1,
+                TokType::RightBrace if depth == 0 => break,
+                TokType::RightParen | TokType::RightBracket | TokType::RightBrace => depth -= 1,
+                TokType::Fun if depth == 0 => {
+                    if let Some(name) = self
+                        .tokens
+                        .get(position + 1)
+                        .filter(|name| name.token_type == TokType::Identifier)
+                    {
+                        names.push(name.lexeme.clone());
+                    }
                 }
+                TokType::Eof => break,
+                _ => (),
             }
         }
+
+        for name in names {
+            self.declare_name(&name);
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{
-        pipeline::scanner::Scanner,
-        types::{Lit, Tok, TokType},
-    };
+    /// How many scopes out from the innermost one the name was declared in, or None if it is a global.
+    fn resolve(&self, name: &Tok) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains(&name.lexeme))
+    }
 
-    use super::Parser;
+    fn var_declaration(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_token(TokType::LeftBracket) || self.match_token(TokType::LeftBrace) {
+            let pattern = self.pattern()?;
 
-    #[test]
-    fn should_add() {
-        let one = Tok {
-            lexeme: "1".to_string(),
-            line: 1,
-            literal: Some(Lit::Number(1.0)),
-            token_type: TokType::Number,
-        };
+            Self::check_distinct_bindings(&pattern)?;
 
-        let plus = Tok {
-            lexeme: "+".to_string(),
-            line: 1,
-            literal: None,
-            token_type: TokType::Plus,
-        };
+            let equals = self.consume(TokType::Equal)?.clone();
+            let expr = self.expression()?;
 
-        let two = Tok {
-            lexeme: "2".to_string(),
-            line: 1,
-            literal: Some(Lit::Number(2.0)),
-            token_type: TokType::Number,
-        };
+            self.consume(TokType::Semicolon)?;
 
-        let semi = Tok {
-            lexeme: ';'.to_string(),
-            line: 1,
-            literal: None,
-            token_type: TokType::Semicolon,
-        };
+            for name in pattern.bindings() {
+                self.declare(name);
+            }
 
-        let scanned_tokens = vec![one, plus, two, semi];
+            return Ok(Stmt::VarPattern {
+                pattern,
+                equals,
+                expr,
+            });
+        }
 
-        let mut parser = Parser::new(&scanned_tokens);
-        let expr = parser.expression();
+        let token = self.consume(TokType::Identifier)?.clone();
 
-        assert_eq!(expr.unwrap().to_string(), "(+ 1 2)");
-    }
+        let initializer = if self.match_token(TokType::Equal) {
+            self.advance();
+            self.expression()?
+        } else {
+            Expr::Literal { value: Lit::Nil }
+        };
 
-    #[test]
-    fn input_from_scanner() {
-        let source = "1 + 2 <= 5 + 7";
-        let mut scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens().unwrap();
-        let mut parser = Parser::new(&tokens);
+        self.consume(TokType::Semicolon)?;
+        self.declare(&token);
+
+        Ok(Stmt::Var {
+            name: token,
+            expr: initializer,
+        })
+    }
+
+    fn statement(&mut self) -> Result<Stmt, ParserError> {
+        self.nested(Self::nested_statement)
+    }
+
+    fn nested_statement(&mut self) -> Result<Stmt, ParserError> {
+        if self.match_tokens_then_advance(&[TokType::If]) {
+            self.if_statement()
+        } else if self.match_tokens_then_advance(&[TokType::While]) {
+            self.while_statement()
+        } else if self.match_tokens_then_advance(&[TokType::For]) {
+            self.for_statement()
+        } else if self.match_tokens_then_advance(&[TokType::Break, TokType::Continue]) {
+            self.loop_control_statement()
+        } else if self.match_tokens_then_advance(&[TokType::Return]) {
+            self.return_statement()
+        } else if self.match_tokens_then_advance(&[TokType::Print]) {
+            self.print_statement()
+        } else if self.match_tokens_then_advance(&[TokType::Throw]) {
+            self.throw_statement()
+        } else if self.match_tokens_then_advance(&[TokType::Try]) {
+            self.try_statement()
+        } else if self.match_tokens_then_advance(&[TokType::LeftBrace]) {
+            Ok(Stmt::Block {
+                stmts: self.scoped(Self::block)?,
+            })
+        } else {
+            self.expression_statement()
+        }
+    }
+
+    /// Collects declarations up to the closing brace. Assumes the opening brace has been consumed.
+    fn block(&mut self) -> Result<Vec<Stmt>, ParserError> {
+        let mut stmts: Vec<Stmt> = vec![];
+
+        self.declare_local_functions();
+
+        while !self.match_token(TokType::RightBrace) && !self.is_at_end() {
+            stmts.push(self.declaration()?);
+        }
+
+        self.consume(TokType::RightBrace)?;
+
+        Ok(stmts)
+    }
+
+    /// An `else` always binds to the nearest preceding `if`, as the else branch is claimed eagerly here.
+    fn if_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokType::LeftParen)?;
+
+        let condition = self.expression()?;
+
+        self.consume(TokType::RightParen)?;
+
+        let then_branch = Box::new(self.statement()?);
+        let else_branch = if self.match_tokens_then_advance(&[TokType::Else]) {
+            Some(Box::new(self.statement()?))
+        } else {
+            None
+        };
+
+        Ok(Stmt::If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn while_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokType::LeftParen)?;
+
+        let condition = self.expression()?;
+
+        self.consume(TokType::RightParen)?;
+
+        Ok(Stmt::While {
+            condition,
+            body: Box::new(self.loop_body()?),
+            increment: None,
+        })
+    }
+
+    /// Desugars `for (init; cond; step) body` into `{ init; while (cond) body step }`.
+    fn for_statement(&mut self) -> Result<Stmt, ParserError> {
+        self.consume(TokType::LeftParen)?;
+
+        if self.match_tokens_then_advance(&[TokType::Semicolon]) {
+            return self.for_loop(None);
+        }
+
+        // The block the loop is wrapped in has a scope of its own, which holds the loop variable
+        self.scoped(|parser| {
+            let initializer = if parser.match_tokens_then_advance(&[TokType::Var]) {
+                parser.var_declaration()?
+            } else {
+                parser.expression_statement()?
+            };
+
+            parser.for_loop(Some(initializer))
+        })
+    }
+
+    /// Parses the rest of a `for` loop after its initializer.
+    fn for_loop(&mut self, initializer: Option<Stmt>) -> Result<Stmt, ParserError> {
+        let condition = if self.match_token(TokType::Semicolon) {
+            Expr::Literal {
+                value: Lit::Bool(true),
+            }
+        } else {
+            self.expression()?
+        };
+
+        self.consume(TokType::Semicolon)?;
+
+        let increment = if self.match_token(TokType::RightParen) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        self.consume(TokType::RightParen)?;
+
+        let while_loop = Stmt::While {
+            condition,
+            body: Box::new(self.loop_body()?),
+            increment,
+        };
+
+        Ok(match initializer {
+            Some(initializer) => Stmt::Block {
+                stmts: vec![initializer, while_loop],
+            },
+            None => while_loop,
+        })
+    }
+
+    fn loop_body(&mut self) -> Result<Stmt, ParserError> {
+        self.loop_depth += 1;
+
+        let body = self.statement();
+
+        self.loop_depth -= 1;
+
+        body
+    }
+
+    fn loop_control_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+
+        if self.loop_depth == 0 {
+            return Err(ParserError::LoopControlOutsideLoop(CodeLocation {
+                line: keyword.line,
+                display: DbgDisplay::from(&keyword),
+            }));
+        }
+
+        self.consume(TokType::Semicolon)?;
+
+        Ok(match keyword.token_type {
+            TokType::Break => Stmt::Break,
+            _ => Stmt::Continue,
+        })
+    }
+
+    fn throw_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+        let expr = self.expression()?;
+
+        self.consume(TokType::Semicolon)?;
+
+        Ok(Stmt::Throw { keyword, expr })
+    }
+
+    fn try_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+
+        self.consume(TokType::LeftBrace)?;
+
+        let body = self.scoped(Self::block)?;
+
+        let catch = if self.match_tokens_then_advance(&[TokType::Catch]) {
+            self.consume(TokType::LeftParen)?;
+
+            let name = self.consume(TokType::Identifier)?.clone();
+
+            self.consume(TokType::RightParen)?;
+            self.consume(TokType::LeftBrace)?;
+
+            // The error is bound in the same scope as the handler's own declarations
+            let handler = self.scoped(|parser| {
+                parser.declare(&name);
+                parser.block()
+            })?;
+
+            Some((name, handler))
+        } else {
+            None
+        };
+
+        let finally = if self.match_tokens_then_advance(&[TokType::Finally]) {
+            self.consume(TokType::LeftBrace)?;
+
+            Some(self.scoped(Self::block)?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParserError::TryWithoutHandler(CodeLocation {
+                line: keyword.line,
+                display: DbgDisplay::from(&keyword),
+            }));
+        }
+
+        Ok(Stmt::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
+    fn return_statement(&mut self) -> Result<Stmt, ParserError> {
+        let keyword = self.previous().clone();
+
+        if self.function_depth == 0 {
+            return Err(ParserError::ReturnOutsideFunction(CodeLocation {
+                line: keyword.line,
+                display: DbgDisplay::from(&keyword),
+            }));
+        }
+
+        let value = if self.match_token(TokType::Semicolon) {
+            Expr::Literal { value: Lit::Nil }
+        } else if self.in_initializer {
+            return Err(ParserError::ReturnValueFromInitializer(CodeLocation {
+                line: keyword.line,
+                display: DbgDisplay::from(&keyword),
+            }));
+        } else {
+            self.expression()?
+        };
+
+        self.consume(TokType::Semicolon)?;
+
+        Ok(Stmt::Return { expr: value })
+    }
+
+    fn print_statement(&mut self) -> Result<Stmt, ParserError> {
+        let value = self.expression()?;
+
+        self.consume(TokType::Semicolon)?;
+
+        Ok(Stmt::Print { expr: value })
+    }
+
+    fn expression_statement(&mut self) -> Result<Stmt, ParserError> {
+        let expr = self.expression()?;
+
+        self.consume(TokType::Semicolon)?;
+
+        Ok(Stmt::Expr { expr })
+    }
+
+    fn expression(&mut self) -> Result<Expr, ParserError> {
+        self.assignment()
+    }
+
+    /// Assignment is right-associative, so the value is parsed by recursing rather than looping.
+    fn assignment(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.conditional()?;
+
+        if self.match_tokens_then_advance(&[TokType::Equal]) {
+            let equals = self.previous().clone();
+            let value = self.nested(Self::assignment)?;
+
+            return match expr {
+                Expr::Variable { name, depth } => Ok(Expr::Assign {
+                    name,
+                    value: Box::new(value),
+                    depth,
+                }),
+                Expr::Get { object, name } => Ok(Expr::Set {
+                    object,
+                    name,
+                    value: Box::new(value),
+                }),
+                Expr::Index {
+                    object,
+                    bracket,
+                    index,
+                } => Ok(Expr::IndexSet {
+                    object,
+                    bracket,
+                    index,
+                    value: Box::new(value),
+                }),
+                Expr::List { .. } => {
+                    let pattern = Self::assignment_pattern(expr, &equals)?;
+
+                    Self::check_distinct_bindings(&pattern)?;
+
+                    let depths = pattern
+                        .bindings()
+                        .into_iter()
+                        .map(|name| self.resolve(name))
+                        .collect();
+
+                    Ok(Expr::AssignPattern {
+                        pattern,
+                        equals,
+                        value: Box::new(value),
+                        depths,
+                    })
+                }
+                _ => Err(ParserError::InvalidAssignmentTarget(CodeLocation {
+                    line: equals.line,
+                    display: DbgDisplay::from(&equals),
+                })),
+            };
+        }
+
+        let compound_types = [
+            TokType::PlusEqual,
+            TokType::MinusEqual,
+            TokType::StarEqual,
+            TokType::SlashEqual,
+            TokType::PercentEqual,
+        ];
+
+        if self.match_tokens_then_advance(&compound_types) {
+            let operator = self.previous().clone();
+            let value = self.nested(Self::assignment)?;
+
+            return Self::update(expr, operator, value, false);
+        }
+
+        Ok(expr)
+    }
+
+    /// Turns the list on the left of a destructuring assignment, like `[a, [b, _]]`, into a pattern.
+    /// Only variables, `_` and nested lists can be assigned to this way.
+    fn assignment_pattern(target: Expr, equals: &Tok) -> Result<Pattern, ParserError> {
+        match target {
+            Expr::Variable { name, .. } if name.lexeme == "_" => Ok(Pattern::Wildcard),
+            Expr::Variable { name, .. } => Ok(Pattern::Binding { name }),
+            Expr::List { elements } => Ok(Pattern::List {
+                elements: elements
+                    .into_iter()
+                    .map(|element| Self::assignment_pattern(element, equals))
+                    .collect::<Result<_, _>>()?,
+                rest: None,
+            }),
+            _ => Err(ParserError::InvalidAssignmentTarget(CodeLocation {
+                line: equals.line,
+                display: DbgDisplay::from(equals),
+            })),
+        }
+    }
+
+    /// Rejects a pattern that binds the same name twice, like `[a, a]`, at the repeated name.
+    fn check_distinct_bindings(pattern: &Pattern) -> Result<(), ParserError> {
+        let names = pattern.bindings();
+
+        for (position, name) in names.iter().enumerate() {
+            if names[..position]
+                .iter()
+                .any(|earlier| earlier.lexeme == name.lexeme)
+            {
+                return Err(ParserError::DuplicateBinding(CodeLocation {
+                    line: name.line,
+                    display: DbgDisplay::from(*name),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds a read-modify-write of `target`, which must be something that can be assigned to.
+    fn update(
+        target: Expr,
+        operator: Tok,
+        value: Expr,
+        postfix: bool,
+    ) -> Result<Expr, ParserError> {
+        match target {
+            Expr::Variable { .. } | Expr::Get { .. } | Expr::Index { .. } => Ok(Expr::Update {
+                target: Box::new(target),
+                operator,
+                value: Box::new(value),
+                postfix,
+            }),
+            _ => Err(ParserError::InvalidAssignmentTarget(CodeLocation {
+                line: operator.line,
+                display: DbgDisplay::from(&operator),
+            })),
+        }
+    }
+
+    /// The implicit value added or subtracted by `++` and `--`.
+    fn one() -> Expr {
+        Expr::Literal {
+            value: Lit::Integer(1),
+        }
+    }
+
+    fn conditional(&mut self) -> Result<Expr, ParserError> {
+        let condition = self.coalesce()?;
+
+        if self.match_tokens_then_advance(&[TokType::Question]) {
+            // Both branches may be assignments, and the else branch may be another conditional,
+            // which makes `a ? b : c ? d : e` group as `a ? b : (c ? d : e)`
+            let then_branch = self.nested(Self::assignment)?;
+
+            self.consume(TokType::Colon)?;
+
+            let else_branch = self.nested(Self::assignment)?;
+
+            return Ok(Expr::Conditional {
+                condition: Box::new(condition),
+                then_branch: Box::new(then_branch),
+                else_branch: Box::new(else_branch),
+            });
+        }
+
+        Ok(condition)
+    }
+
+    fn coalesce(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.or()?;
+        let outer = self.nesting;
+
+        while self.match_tokens_then_advance(&[TokType::QuestionQuestion]) {
+            self.deepen()?;
+
+            let operator = self.previous().clone();
+            let right = self.or()?;
+
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
+        }
+
+        self.nesting = outer;
+
+        Ok(expr)
+    }
+
+    fn or(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.and()?;
+        let outer = self.nesting;
+
+        while self.match_tokens_then_advance(&[TokType::Or]) {
+            self.deepen()?;
+
+            let operator = self.previous().clone();
+            let right = self.and()?;
+
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
+        }
+
+        self.nesting = outer;
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.equality()?;
+        let outer = self.nesting;
+
+        while self.match_tokens_then_advance(&[TokType::And]) {
+            self.deepen()?;
+
+            let operator = self.previous().clone();
+            let right = self.equality()?;
+
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
+        }
+
+        self.nesting = outer;
+
+        Ok(expr)
+    }
+
+    // Binary operators from loosest to tightest:
+    //   == !=   < <= > >=   |   ^   &   << >>   + -   * / %   unary ! - ~   **
+    // Bitwise operators bind tighter than comparisons, so `x & 1 == 0` means `(x & 1) == 0`.
+    // `**` binds tighter than unary minus, so `-2 ** 2` is `-(2 ** 2)`, but its right operand
+    // may itself be unary, so `2 ** -1` works. It is right-associative: `2 ** 3 ** 2` is `2 ** 9`.
+
+    fn equality(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::BangEqual, TokType::EqualEqual], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, ParserError> {
+        let token_types = [
+            TokType::Greater,
+            TokType::GreaterEqual,
+            TokType::Less,
+            TokType::LessEqual,
+        ];
+
+        self.binary(&token_types, Self::bitwise_or)
+    }
+
+    fn bitwise_or(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::Pipe], Self::bitwise_xor)
+    }
+
+    fn bitwise_xor(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::Caret], Self::bitwise_and)
+    }
+
+    fn bitwise_and(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::Ampersand], Self::shift)
+    }
+
+    fn shift(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::LessLess, TokType::GreaterGreater], Self::term)
+    }
+
+    fn term(&mut self) -> Result<Expr, ParserError> {
+        self.binary(&[TokType::Minus, TokType::Plus], Self::factor)
+    }
+
+    fn factor(&mut self) -> Result<Expr, ParserError> {
+        let token_types = [TokType::Star, TokType::Slash, TokType::Percent];
+
+        self.binary(&token_types, Self::unary)
+    }
+
+    /// Parses a left-associative chain of the given operators, with `operand` parsing each side.
+    fn binary(
+        &mut self,
+        token_types: &[TokType],
+        operand: fn(&mut Self) -> Result<Expr, ParserError>,
+    ) -> Result<Expr, ParserError> {
+        let mut expr = operand(self)?;
+        let outer = self.nesting;
+
+        while self.match_tokens_then_advance(token_types) {
+            // Each operator wraps the chain so far in another node, one level deeper
+            self.deepen()?;
+
+            let operator = self.previous().clone();
+            let right = operand(self)?;
+
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            }
+        }
+
+        self.nesting = outer;
+
+        Ok(expr)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParserError> {
+        self.nested(Self::nested_unary)
+    }
+
+    fn nested_unary(&mut self) -> Result<Expr, ParserError> {
+        if self.match_tokens_then_advance(&[TokType::PlusPlus, TokType::MinusMinus]) {
+            let operator = self.previous().clone();
+            let target = self.unary()?;
+
+            return Self::update(target, operator, Self::one(), false);
+        }
+
+        let token_types = [TokType::Bang, TokType::Minus, TokType::Tilde];
+
+        if self.match_tokens_then_advance(&token_types) {
+            let operator = self.previous().clone();
+            let right = self.unary()?;
+
+            let unary = Expr::Unary {
+                operator,
+                right: Box::new(right),
+            };
+
+            return Ok(unary);
+        }
+
+        self.power()
+    }
+
+    fn power(&mut self) -> Result<Expr, ParserError> {
+        let expr = self.postfix()?;
+
+        if self.match_tokens_then_advance(&[TokType::StarStar]) {
+            let operator = self.previous().clone();
+            // Recursing through unary makes this right-associative
+            let right = self.unary()?;
+
+            return Ok(Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            });
+        }
+
+        Ok(expr)
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.call()?;
+
+        // Looping means `x++ ++` reports the second `++` as having an invalid target
+        while self.match_tokens_then_advance(&[TokType::PlusPlus, TokType::MinusMinus]) {
+            let operator = self.previous().clone();
+
+            expr = Self::update(expr, operator, Self::one(), true)?;
+        }
+
+        Ok(expr)
+    }
+
+    fn call(&mut self) -> Result<Expr, ParserError> {
+        let mut expr = self.primary()?;
+        let outer = self.nesting;
+
+        loop {
+            if self.match_tokens_then_advance(&[TokType::LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_tokens_then_advance(&[TokType::LeftBracket]) {
+                let bracket = self.previous().clone();
+                let index = self.expression()?;
+
+                self.consume(TokType::RightBracket)?;
+
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                };
+            } else if self.match_tokens_then_advance(&[TokType::Dot]) {
+                let name = self.consume(TokType::Identifier)?.clone();
+
+                expr = Expr::Get {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else if self.match_tokens_then_advance(&[TokType::QuestionDot]) {
+                let name = self.consume(TokType::Identifier)?.clone();
+
+                expr = Expr::OptionalGet {
+                    object: Box::new(expr),
+                    name,
+                };
+            } else {
+                break;
+            }
+
+            // Each call, index or property access wraps the expression so far, one level deeper
+            self.deepen()?;
+        }
+
+        self.nesting = outer;
+
+        Ok(expr)
+    }
+
+    /// Parses the argument list of a call. Assumes the opening paren has been consumed.
+    /// Named arguments come after the positional and spread ones.
+    fn finish_call(&mut self, callee: Expr) -> Result<Expr, ParserError> {
+        let mut args: Vec<Argument> = vec![];
+
+        while !self.match_token(TokType::RightParen) {
+            let arg = if self.match_token(TokType::Identifier) && self.check_next(TokType::Colon) {
+                let name = self.advance().clone();
+
+                self.advance();
+
+                Argument::Named {
+                    name,
+                    value: self.expression()?,
+                }
+            } else {
+                let start = self.peek().clone();
+
+                if matches!(args.last(), Some(Argument::Named { .. })) {
+                    return Err(ParserError::PositionalAfterNamed(CodeLocation {
+                        line: start.line,
+                        display: DbgDisplay::from(&start),
+                    }));
+                }
+
+                if self.match_tokens_then_advance(&[TokType::DotDot]) {
+                    Argument::Spread(self.expression()?)
+                } else {
+                    Argument::Positional(self.expression()?)
+                }
+            };
+
+            args.push(arg);
+
+            if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                break;
+            }
+        }
+
+        let paren = self.consume(TokType::RightParen)?.clone();
+
+        Ok(Expr::Call {
+            callee: Box::new(callee),
+            paren,
+            args,
+        })
+    }
+
+    fn primary(&mut self) -> Result<Expr, ParserError> {
+        if self.match_tokens_then_advance(&[TokType::Identifier]) {
+            let name = self.previous().clone();
+
+            return Ok(Expr::Variable {
+                depth: self.resolve(&name),
+                name,
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::True]) {
+            return Ok(Expr::Literal {
+                value: Lit::Bool(true),
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::False]) {
+            return Ok(Expr::Literal {
+                value: Lit::Bool(false),
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Nil]) {
+            return Ok(Expr::Literal { value: Lit::Nil });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Super]) {
+            let keyword = self.previous().clone();
+
+            if !self.in_subclass {
+                return Err(ParserError::SuperOutsideSubclass(CodeLocation {
+                    line: keyword.line,
+                    display: DbgDisplay::from(&keyword),
+                }));
+            }
+
+            self.consume(TokType::Dot)?;
+
+            let method = self.consume(TokType::Identifier)?.clone();
+
+            return Ok(Expr::Super {
+                depth: self.resolve(&keyword),
+                keyword,
+                method,
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::This]) {
+            let keyword = self.previous().clone();
+
+            if self.class_depth == 0 {
+                return Err(ParserError::ThisOutsideClass(CodeLocation {
+                    line: keyword.line,
+                    display: DbgDisplay::from(&keyword),
+                }));
+            }
+
+            return Ok(Expr::This {
+                depth: self.resolve(&keyword),
+                keyword,
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Interpolation]) {
+            return self.interpolation();
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Match]) {
+            return self.match_expression();
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Number, TokType::String]) {
+            return Ok(Expr::Literal {
+                value: self
+                    .previous()
+                    .literal
+                    .clone()
+                    .ok_or(ParserError::UndefinedLiteral(CodeLocation {
+                        line: self.peek().line,
+                        display: DbgDisplay::from(self.peek()),
+                    }))?,
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::LeftBracket]) {
+            let mut elements: Vec<Expr> = vec![];
+
+            if !self.match_token(TokType::RightBracket) {
+                loop {
+                    elements.push(self.expression()?);
+
+                    if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(TokType::RightBracket)?;
+
+            return Ok(Expr::List { elements });
+        }
+
+        // Only reached in expression position. A `{` at the start of a statement is always a block.
+        if self.match_tokens_then_advance(&[TokType::LeftBrace]) {
+            let brace = self.previous().clone();
+            let mut entries: Vec<(Expr, Expr)> = vec![];
+
+            if !self.match_token(TokType::RightBrace) {
+                loop {
+                    let key = self.expression()?;
+
+                    self.consume(TokType::Colon)?;
+
+                    entries.push((key, self.expression()?));
+
+                    if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                        break;
+                    }
+                }
+            }
+
+            self.consume(TokType::RightBrace)?;
+
+            return Ok(Expr::Map { brace, entries });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::Fun]) {
+            let keyword = self.previous().clone();
+
+            self.consume(TokType::LeftParen)?;
+
+            let (params, body) = self.function_scope(false, |parser| {
+                parser.consume(TokType::LeftBrace)?;
+                parser.block()
+            })?;
+
+            return Ok(Expr::Lambda {
+                keyword,
+                params,
+                body: Rc::new(body),
+            });
+        }
+
+        if self.match_token(TokType::LeftParen) && self.is_arrow_function() {
+            self.advance();
+
+            // The body is a single expression. A `{` here starts a map, not a block
+            let (params, (arrow, expr)) = self.function_scope(false, |parser| {
+                let arrow = parser.consume(TokType::EqualGreater)?.clone();
+
+                Ok((arrow, parser.expression()?))
+            })?;
+
+            return Ok(Expr::Lambda {
+                keyword: arrow,
+                params,
+                body: Rc::new(vec![Stmt::Return { expr }]),
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::LeftParen]) {
+            let expr = self.expression()?;
+
+            self.consume(TokType::RightParen)?;
+
+            return Ok(Expr::Grouping {
+                expression: Box::new(expr),
+            });
+        }
+
+        Err(ParserError::PrimaryError(CodeLocation {
+            line: self.peek().line,
+            display: DbgDisplay::from(self.peek()),
+        }))
+    }
+
+    /// Parses the fragments and embedded expressions of an interpolated string.
+    /// Assumes the first fragment has been consumed. The final fragment is a plain string token.
+    fn interpolation(&mut self) -> Result<Expr, ParserError> {
+        let mut parts: Vec<Expr> = vec![];
+
+        loop {
+            if let Some(Lit::String(fragment)) = &self.previous().literal {
+                if !fragment.is_empty() {
+                    parts.push(Expr::Literal {
+                        value: Lit::String(fragment.clone()),
+                    });
+                }
+            }
+
+            if self.previous().token_type == TokType::String {
+                return Ok(Expr::Interpolation { parts });
+            }
+
+            parts.push(self.expression()?);
+
+            if !self.match_tokens_then_advance(&[TokType::Interpolation]) {
+                self.consume(TokType::String)?;
+            }
+        }
+    }
+
+    /// Parses the subject and arms of a match expression. Assumes the keyword has been consumed.
+    fn match_expression(&mut self) -> Result<Expr, ParserError> {
+        let keyword = self.previous().clone();
+        let subject = self.expression()?;
+        let mut arms: Vec<MatchArm> = vec![];
+
+        self.consume(TokType::LeftBrace)?;
+
+        while !self.match_token(TokType::RightBrace) {
+            let pattern = self.pattern()?;
+
+            Self::check_distinct_bindings(&pattern)?;
+
+            // The pattern's bindings are scoped to the arm's guard and body
+            let (guard, body) = self.scoped(|parser| {
+                for name in pattern.bindings() {
+                    parser.declare(name);
+                }
+
+                let guard = if parser.match_tokens_then_advance(&[TokType::If]) {
+                    let enclosing_guard_start = parser.guard_start.replace(parser.current);
+                    let guard = parser.expression();
+
+                    parser.guard_start = enclosing_guard_start;
+
+                    Some(guard?)
+                } else {
+                    None
+                };
+
+                parser.consume(TokType::EqualGreater)?;
+
+                Ok((guard, parser.expression()?))
+            })?;
+
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(TokType::RightBrace)?;
+
+        self.check_exhaustive(&keyword, &arms);
+
+        Ok(Expr::Match {
+            keyword,
+            subject: Box::new(subject),
+            arms,
+        })
+    }
+
+    /// Warns when a match is over a closed set of values, booleans or the variants of an enum declared earlier,
+    /// and misses some of them without a catch-all arm. Other matches can't be checked as the subject's type is
+    /// only known at runtime.
+    fn check_exhaustive(&mut self, keyword: &Tok, arms: &[MatchArm]) {
+        let mut cases: Option<Vec<String>> = None;
+        let mut handled: Vec<String> = vec![];
+
+        for arm in arms {
+            if arm.guard.is_none() && arm.pattern.is_irrefutable() {
+                return;
+            }
+
+            // The closed set the pattern belongs to, the case it names, and whether it matches all of that case
+            let (arm_cases, case, covers) = match &arm.pattern {
+                Pattern::Literal {
+                    value: Lit::Bool(value),
+                } => (
+                    vec!["true".to_string(), "false".to_string()],
+                    value.to_string(),
+                    true,
+                ),
+                Pattern::Variant {
+                    enumeration,
+                    name,
+                    fields,
+                    ..
+                } => {
+                    let Some(variants) = self.enums.get(&enumeration.lexeme) else {
+                        return;
+                    };
+
+                    (
+                        variants
+                            .iter()
+                            .map(|variant| format!("{}.{}", enumeration.lexeme, variant))
+                            .collect(),
+                        format!("{}.{}", enumeration.lexeme, name.lexeme),
+                        fields.iter().flatten().all(Pattern::is_irrefutable),
+                    )
+                }
+                _ => return,
+            };
+
+            if *cases.get_or_insert_with(|| arm_cases.clone()) != arm_cases {
+                return;
+            }
+
+            if covers && arm.guard.is_none() {
+                handled.push(case);
+            }
+        }
+
+        let missing: Vec<String> = cases
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|case| !handled.contains(case))
+            .collect();
+
+        if !missing.is_empty() {
+            self.warnings.push(ParserWarning::NonExhaustiveMatch(
+                CodeLocation {
+                    line: keyword.line,
+                    display: DbgDisplay::from(keyword),
+                },
+                missing.join(" or "),
+            ));
+        }
+    }
+
+    fn pattern(&mut self) -> Result<Pattern, ParserError> {
+        if self.match_tokens_then_advance(&[TokType::Identifier]) {
+            let name = self.previous().clone();
+
+            if self.match_tokens_then_advance(&[TokType::Dot]) {
+                return self.variant_pattern(name);
+            }
+
+            return Ok(if name.lexeme == "_" {
+                Pattern::Wildcard
+            } else {
+                Pattern::Binding { name }
+            });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::LeftBrace]) {
+            let mut fields: Vec<(Tok, Pattern)> = vec![];
+
+            while !self.match_token(TokType::RightBrace) {
+                let key = self.consume(TokType::Identifier)?.clone();
+
+                let pattern = if self.match_tokens_then_advance(&[TokType::Colon]) {
+                    self.pattern()?
+                } else {
+                    Pattern::Binding { name: key.clone() }
+                };
+
+                fields.push((key, pattern));
+
+                if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(TokType::RightBrace)?;
+
+            return Ok(Pattern::Record { fields });
+        }
+
+        if self.match_tokens_then_advance(&[TokType::LeftBracket]) {
+            let mut elements: Vec<Pattern> = vec![];
+            let mut rest = None;
+
+            while !self.match_token(TokType::RightBracket) {
+                // The rest pattern has to come last, so the closing bracket must follow it
+                if self.match_tokens_then_advance(&[TokType::DotDot]) {
+                    rest = Some(Box::new(if self.match_token(TokType::RightBracket) {
+                        Pattern::Wildcard
+                    } else {
+                        self.pattern()?
+                    }));
+
+                    break;
+                }
+
+                elements.push(self.pattern()?);
+
+                if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(TokType::RightBracket)?;
+
+            return Ok(Pattern::List { elements, rest });
+        }
+
+        let start = self.literal_pattern()?;
+
+        if self.match_tokens_then_advance(&[TokType::DotDot, TokType::DotDotEqual]) {
+            let inclusive = self.previous().token_type == TokType::DotDotEqual;
+            let end = self.literal_pattern()?;
+
+            if !start.is_number() || !end.is_number() {
+                return Err(self.invalid_pattern());
+            }
+
+            return Ok(Pattern::Range {
+                start,
+                end,
+                inclusive,
+            });
+        }
+
+        Ok(Pattern::Literal { value: start })
+    }
+
+    /// Parses the rest of a pattern like `Shape.Circle(r)`. Assumes the enum name and dot have been consumed.
+    fn variant_pattern(&mut self, enumeration: Tok) -> Result<Pattern, ParserError> {
+        let name = self.consume(TokType::Identifier)?.clone();
+
+        let fields = if self.match_tokens_then_advance(&[TokType::LeftParen]) {
+            let mut fields: Vec<Pattern> = vec![];
+
+            while !self.match_token(TokType::RightParen) {
+                fields.push(self.pattern()?);
+
+                if !self.match_tokens_then_advance(&[TokType::Comma]) {
+                    break;
+                }
+            }
+
+            self.consume(TokType::RightParen)?;
+
+            Some(fields)
+        } else {
+            None
+        };
+
+        Ok(Pattern::Variant {
+            depth: self.resolve(&enumeration),
+            enumeration,
+            name,
+            fields,
+        })
+    }
+
+    /// Parses a literal in a pattern. Numbers may be negative, like `-1`.
+    fn literal_pattern(&mut self) -> Result<Lit, ParserError> {
+        if self.match_tokens_then_advance(&[TokType::Minus]) {
+            let value = self.literal_pattern()?;
+
+            return negate(&value).map_err(|_| self.invalid_pattern());
+        }
+
+        let value = match self.peek().token_type {
+            TokType::True => Lit::Bool(true),
+            TokType::False => Lit::Bool(false),
+            TokType::Nil => Lit::Nil,
+            TokType::Number | TokType::String => self
+                .peek()
+                .literal
+                .clone()
+                .ok_or_else(|| self.invalid_pattern())?,
+            _ => return Err(self.invalid_pattern()),
+        };
+
+        self.advance();
+
+        Ok(value)
+    }
+
+    fn invalid_pattern(&self) -> ParserError {
+        ParserError::InvalidPattern(CodeLocation {
+            line: self.peek().line,
+            display: DbgDisplay::from(self.peek()),
+        })
+    }
+
+    fn synchronise(&mut self) {
+        self.advance();
+
+        while !self.is_at_end() {
+            if self.previous().token_type == TokType::Semicolon {
+                return;
+            }
+
+            match self.peek().token_type {
+                TokType::Class
+                | TokType::Enum
+                | TokType::Fun
+                | TokType::Var
+                | TokType::For
+                | TokType::If
+                | TokType::While
+                | TokType::Print
+                | TokType::Return
+                | TokType::Throw
+                | TokType::Try => return,
+                _ => {
+                    self.advance();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        pipeline::scanner::Scanner,
+        types::{Lit, Stmt, Tok, TokType},
+    };
+
+    use super::{Parser, ParserError};
+
+    #[test]
+    fn should_add() {
+        let one = Tok {
+            lexeme: "1".to_string(),
+            line: 1,
+            literal: Some(Lit::Number(1.0)),
+            token_type: TokType::Number,
+        };
+
+        let plus = Tok {
+            lexeme: "+".to_string(),
+            line: 1,
+            literal: None,
+            token_type: TokType::Plus,
+        };
+
+        let two = Tok {
+            lexeme: "2".to_string(),
+            line: 1,
+            literal: Some(Lit::Number(2.0)),
+            token_type: TokType::Number,
+        };
+
+        let semi = Tok {
+            lexeme: ';'.to_string(),
+            line: 1,
+            literal: None,
+            token_type: TokType::Semicolon,
+        };
+
+        let scanned_tokens = vec![one, plus, two, semi];
+
+        let mut parser = Parser::new(&scanned_tokens);
+        let expr = parser.expression();
+
+        assert_eq!(expr.unwrap().to_string(), "(+ 1 2)");
+    }
+
+    #[test]
+    fn input_from_scanner() {
+        let source = "1 + 2 <= 5 + 7";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(expr.unwrap().to_string(), "(<= (+ 1 2) (+ 5 7))");
+    }
+
+    #[test]
+    fn input_from_scanner_with_parens() {
+        let source = "1 + (2 + 2) == 5";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(expr.unwrap().to_string(), "(== (+ 1 (group (+ 2 2))) 5)");
+    }
+
+    #[test]
+    fn should_parse_operator_precedence() {
+        let cases = [
+            ("1 * 2 + 3 * 4", "(+ (* 1 2) (* 3 4))"),
+            ("1 - 2 - 3", "(- (- 1 2) 3)"),
+            ("7 % 3 * 2", "(* (% 7 3) 2)"),
+            ("-2 ** 2", "(- (** 2 2))"),
+            ("2 ** 3 ** 2", "(** 2 (** 3 2))"),
+            ("2 ** -1", "(** 2 (- 1))"),
+            ("1 | 2 ^ 3 & 4 << 5 + 6", "(| 1 (^ 2 (& 3 (<< 4 (+ 5 6)))))"),
+            ("3 & 1 == 0", "(== (& 3 1) 0)"),
+            ("~8 >> 1", "(>> (~ 8) 1)"),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let expr = parser.expression();
+
+            assert_eq!(expr.unwrap().to_string(), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn should_parse_compound_assignment_and_increments() {
+        let cases = [
+            ("x += 1 + 2", "(+= (var x) (+ 1 2))"),
+            ("x -= y *= 2", "(-= (var x) (*= (var y) 2))"),
+            ("p.count++", "(post++ (. (var p) count))"),
+            ("--xs[0]", "(-- ([] (var xs) 0))"),
+            ("-x++", "(- (post++ (var x)))"),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let expr = parser.expression();
+
+            assert_eq!(expr.unwrap().to_string(), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn should_error_on_invalid_update_target() {
+        for (source, expected) in [
+            ("(a + b)++;", "At '++', line 1"),
+            ("\n1 += 2;", "At '+=', line 2"),
+            ("++f();", "At '++', line 1"),
+            ("x++ ++;", "At '++', line 1"),
+        ] {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+
+            let error = parser.parse().err().unwrap().to_string();
+
+            assert!(error.contains("Invalid assignment target"), "{source}");
+            assert!(error.contains(expected), "{source}: {error}");
+        }
+    }
+
+    #[test]
+    fn should_parse_conditional_and_nil_operators() {
+        let cases = [
+            ("a ? 1 : b ? 2 : 3", "(?: (var a) 1 (?: (var b) 2 3))"),
+            ("a ?? b or c", "(?? (var a) (or (var b) (var c)))"),
+            ("a ?? b ?? c", "(?? (?? (var a) (var b)) (var c))"),
+            ("a?.b.c()", "(call (. (?. (var a) b) c))"),
+            ("a ? b : c = 1", "(?: (var a) (var b) (= c 1))"),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let expr = parser.expression();
+
+            assert_eq!(expr.unwrap().to_string(), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn should_tell_arrow_functions_from_groupings() {
+        let cases = [
+            ("(a) => a + 1", "(fun (a))"),
+            ("() => 1", "(fun ())"),
+            ("(a)", "(group (var a))"),
+            (
+                "(a + (b)) * c",
+                "(* (group (+ (var a) (group (var b)))) (var c))",
+            ),
+            (
+                "f((x, y) => x, (z))",
+                "(call (var f) (fun (x y)) (group (var z)))",
+            ),
+            ("fun (x) { return x; }", "(fun (x))"),
+            (
+                "match a { x if (x) => (y) => y }",
+                "(match (var a) (x if (group (var x)) (fun (y))))",
+            ),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let expr = parser.expression();
+
+            assert_eq!(expr.unwrap().to_string(), expected, "{source}");
+        }
+    }
+
+    #[test]
+    fn should_parse_match_patterns() {
+        let source = "match x { 0 => a, -2..=2 => b, [first, ..rest] => c, [_, ..] => d, y if y > 1 => e, S.A(1, [z]) => g, S.B => h, _ => f }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
         let expr = parser.expression();
 
-        assert_eq!(expr.unwrap().to_string(), "(<= (+ 1 2) (+ 5 7))");
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(match (var x) (0 (var a)) (-2..=2 (var b)) ([first, ..rest] (var c)) ([_, ..] (var d)) (y if (> (var y) 1) (var e)) (S.A(1, [z]) (var g)) (S.B (var h)) (_ (var f)))"
+        );
     }
 
     #[test]
-    fn input_from_scanner_with_parens() {
-        let source = "1 + (2 + 2) == 5";
+    fn should_parse_destructuring() {
+        let source = "var [a, ..rest] = xs; var {name, age: [y, _]} = p; [a, [b, _]] = [b, a];";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        let patterns: Vec<String> = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::VarPattern { pattern, .. } => pattern.to_string(),
+                Stmt::Expr { expr } => expr.to_string(),
+                _ => panic!("Expected a destructuring statement"),
+            })
+            .collect();
+
+        assert_eq!(
+            patterns,
+            [
+                "[a, ..rest]",
+                "{name, age: [y, _]}",
+                "(= [a, [b, _]] (list (var b) (var a)))"
+            ]
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_destructuring_target() {
+        for source in ["[a, b + 1] = xs;", "[a, xs[0]] = xs;", "var [a, b];"] {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+
+            assert!(parser.parse().is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn should_reject_duplicate_pattern_bindings() {
+        for source in [
+            "var [a, a] = xs;",
+            "var {a, b: [a]} = m;",
+            "[a, [b, a]] = xs;",
+            "match x { [a, ..a] => 1 }",
+        ] {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let error = parser.parse().err().unwrap().to_string();
+
+            assert!(error.contains("bound more than once"), "{source}: {error}");
+        }
+    }
+
+    #[test]
+    fn should_reject_invalid_patterns() {
+        for source in [
+            "match x { a.1 => 1 }",
+            "match x { \"a\"..\"z\" => 1 }",
+            "match x { [..a, b] => 1 }",
+        ] {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+
+            assert!(parser.expression().is_err(), "{source}");
+        }
+    }
+
+    #[test]
+    fn should_warn_on_non_exhaustive_boolean_match() {
+        let source = "print match a { true => 1 }; print match b { true => 1, x => 2 }; print match c { true => 1, false => 0 };";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse().unwrap();
+
+        let warnings: Vec<String> = parser.warnings().iter().map(|w| w.to_string()).collect();
+
+        assert_eq!(
+            warnings,
+            ["Match does not handle false. \nAt 'match', line 1"]
+        );
+    }
+
+    #[test]
+    fn should_error_on_optional_assignment_target() {
+        let source = "a?.b = 1;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("Invalid assignment target"));
+    }
+
+    #[test]
+    fn should_error_on_try_without_catch_or_finally() {
+        let source = "try { print 1; } print 2;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("Try must be followed by catch or finally"));
+    }
+
+    #[test]
+    fn should_parse_assignment_right_associative() {
+        let source = "a = b = 1 + 2";
         let mut scanner = Scanner::new(source);
         let tokens = scanner.scan_tokens().unwrap();
-        let mut parser = Parser::new(&tokens);
+        let mut parser = Parser::new(tokens);
         let expr = parser.expression();
 
-        assert_eq!(expr.unwrap().to_string(), "(== (+ 1 (group (+ 2 2))) 5)");
+        assert_eq!(expr.unwrap().to_string(), "(= a (= b (+ 1 2)))");
+    }
+
+    #[test]
+    fn should_reject_invalid_assignment_target() {
+        let source = "1 + a = 2";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert!(matches!(expr, Err(ParserError::InvalidAssignmentTarget(_))));
+    }
+
+    #[test]
+    fn should_bind_dangling_else_to_nearest_if() {
+        let source = "if (a) if (b) print 1; else print 2;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        let Stmt::If {
+            then_branch,
+            else_branch,
+            ..
+        } = &stmts[0]
+        else {
+            panic!("Expected an if statement");
+        };
+
+        assert!(else_branch.is_none());
+        assert!(matches!(
+            then_branch.as_ref(),
+            Stmt::If {
+                else_branch: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn should_desugar_for_into_while() {
+        let source = "for (var i = 0; i < 3; i = i + 1) print i;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        let Stmt::Block { stmts } = &stmts[0] else {
+            panic!("Expected the initializer to be scoped to a block");
+        };
+
+        assert!(matches!(&stmts[0], Stmt::Var { .. }));
+        assert!(matches!(
+            &stmts[1],
+            Stmt::While {
+                increment: Some(_),
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn should_reject_loop_control_outside_loop() {
+        let source = "break; while (true) { if (true) continue; }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("outside of a loop"));
+        assert!(!error.contains("continue"));
+    }
+
+    #[test]
+    fn should_bind_and_tighter_than_or() {
+        let source = "a or b and c == d";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(or (var a) (and (var b) (== (var c) (var d))))"
+        );
+    }
+
+    #[test]
+    fn should_parse_chained_calls() {
+        let source = "curry(1)(2, a + b)()";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(call (call (call (var curry) 1) 2 (+ (var a) (var b))))"
+        );
+    }
+
+    #[test]
+    fn should_parse_function_declaration() {
+        let source = "fun add(a, b) { return a + b; }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        assert!(matches!(
+            &stmts[0],
+            Stmt::Function { name, params, body }
+                if name.lexeme == "add" && params.len() == 2 && body.len() == 1
+        ));
+    }
+
+    #[test]
+    fn should_parse_enum_declaration() {
+        let source = "enum Shape { Circle(r), Rect(w, h), Empty, }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        let Stmt::Enum { name, variants } = &stmts[0] else {
+            panic!("Expected an enum declaration");
+        };
+
+        let shape: Vec<(&str, usize)> = variants
+            .iter()
+            .map(|(variant, fields)| (variant.lexeme.as_str(), fields.len()))
+            .collect();
+
+        assert_eq!(name.lexeme, "Shape");
+        assert_eq!(shape, [("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+    }
+
+    #[test]
+    fn should_reject_duplicate_variant() {
+        let source = "enum Light { Red, Green, Red }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("already declared in the enum"));
+    }
+
+    #[test]
+    fn should_reject_duplicate_variant_field() {
+        let source = "enum S { A(x, y), B(x, x) }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("already declared in the variant"), "{error}");
+    }
+
+    #[test]
+    fn should_warn_on_match_missing_enum_variants() {
+        let source = r#"
+            enum Light { Red, Amber, Green(seconds) }
+            print match a { Light.Red => 1, Light.Green(0) => 2, Light.Amber if b => 3 };
+            print match a { Light.Red => 1, Light.Amber => 2, Light.Green(_) => 3 };
+            print match a { Light.Red => 1, _ => 2 };
+            print match a { Other.Red => 1 };
+        "#;
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        parser.parse().unwrap();
+
+        let warnings: Vec<String> = parser.warnings().iter().map(|w| w.to_string()).collect();
+
+        assert_eq!(
+            warnings,
+            ["Match does not handle Light.Amber or Light.Green. \nAt 'match', line 3"]
+        );
+    }
+
+    #[test]
+    fn should_parse_parameters_and_arguments() {
+        let source = "f(1, ..xs, b: (a, c = 2, ..d) => a);";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        let Stmt::Expr { expr } = &stmts[0] else {
+            panic!("Expected an expression statement");
+        };
+
+        assert_eq!(
+            expr.to_string(),
+            "(call (var f) 1 ..(var xs) b: (fun (a c = 2 ..d)))"
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_parameters_and_arguments() {
+        let cases = [
+            ("fun f(..a, b) {}", "rest parameter must be the last"),
+            ("fun f(a = 1, b) {}", "without a default cannot follow"),
+            (
+                "fun f(a, a) {}",
+                "parameter with this name is already declared",
+            ),
+            (
+                "fun f(a, a = 1) {}",
+                "parameter with this name is already declared",
+            ),
+            (
+                "(a, ..a) => a;",
+                "parameter with this name is already declared",
+            ),
+            ("f(a: 1, 2);", "Positional arguments must come before named"),
+            (
+                "f(a: 1, ..b);",
+                "Positional arguments must come before named",
+            ),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+            let tokens = scanner.scan_tokens().unwrap();
+            let mut parser = Parser::new(tokens);
+            let error = parser.parse().err().unwrap().to_string();
+
+            assert!(error.contains(expected), "{source}: {error}");
+        }
+    }
+
+    #[test]
+    fn should_reject_return_outside_function() {
+        let source = "return 1;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("outside of a function"));
+    }
+
+    #[test]
+    fn should_reject_break_in_function_inside_loop() {
+        let source = "while (true) { fun inner() { break; } }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn should_parse_property_chain_assignment() {
+        let source = "a.b(1).c = 2";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(= (. (call (. (var a) b) 1) c) 2)"
+        );
+    }
+
+    #[test]
+    fn should_reject_this_outside_class() {
+        let source = "fun f() { return this; }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("outside of a class"));
+    }
+
+    #[test]
+    fn should_reject_returning_value_from_initializer() {
+        let source = "class A { init() { return 1; } other() { return 1; } }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("from an initializer. \nAt 'return', line 1"));
+    }
+
+    #[test]
+    fn should_reject_self_inheritance() {
+        let source = "class A < A {}";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("cannot inherit from itself"));
+    }
+
+    #[test]
+    fn should_reject_super_without_superclass() {
+        let source = "class A { method() { return super.method(); } }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert!(error.contains("Super used outside"));
+    }
+
+    #[test]
+    fn should_parse_list_indexing_and_assignment() {
+        let source = "xs[0][i + 1] = [1, [2], []]";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(= ([] ([] (var xs) 0) (+ (var i) 1)) (list 1 (list 2) (list)))"
+        );
+    }
+
+    #[test]
+    fn should_parse_map_literal_in_expression_and_block_in_statement() {
+        let source = r#"var m = {"a": 1, "b": {}}; { m["c"] = 3; }"#;
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        assert!(matches!(
+            &stmts[0],
+            Stmt::Var { expr, .. } if expr.to_string() == "(map (a 1) (b (map)))"
+        ));
+        assert!(matches!(&stmts[1], Stmt::Block { .. }));
+    }
+
+    #[test]
+    fn should_parse_interpolated_string() {
+        let source = r#""Hello ${name}, you have ${count + 1} items""#;
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let expr = parser.expression();
+
+        assert_eq!(
+            expr.unwrap().to_string(),
+            "(interpolate Hello  (var name) , you have  (+ (var count) 1)  items)"
+        );
+    }
+
+    #[test]
+    fn should_parse_nested_blocks() {
+        let source = "{ var a = 1; { print a; } }";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let stmts = parser.parse().unwrap();
+
+        assert_eq!(stmts.len(), 1);
+        assert!(matches!(&stmts[0], Stmt::Block { stmts } if stmts.len() == 2));
+    }
+
+    #[test]
+    fn should_skip_to_next_statement_after_error() {
+        let source = "print 1 2 3 4 5; print 6;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+        let error = parser.parse().err().unwrap().to_string();
+
+        assert_eq!(error.matches("line 1").count(), 1, "{error}");
+    }
+
+    #[test]
+    fn should_error_on_unclosed_block() {
+        let source = "{ var a = 1;";
+        let mut scanner = Scanner::new(source);
+        let tokens = scanner.scan_tokens().unwrap();
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse().is_err());
     }
 }
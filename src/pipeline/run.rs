@@ -1,15 +1,112 @@
-use super::{interpreter::Interpreter, parser::Parser, scanner::Scanner};
+use std::{panic, thread};
 
+use super::{
+    interpreter::{Interpreter, MAX_CALL_DEPTH},
+    parser::{Parser, MAX_NESTING},
+    scanner::Scanner,
+};
+
+/// Native stack for each nested call. In an unoptimised build a recursive call made from inside a conditional
+/// and an addition was measured at about 92KB, and a bare `return f(n - 1)` at about 44KB.
+const STACK_PER_CALL: usize = 128 * 1024;
+
+/// Native stack for each level of nesting. In an unoptimised build a level of parentheses was measured at about
+/// 94KB to parse, the costliest construct, and about 24KB to evaluate.
+const STACK_PER_NESTING: usize = 96 * 1024;
+
+/// Runs the script on its own thread, whose stack fits calls nested up to the interpreter's limit
+/// as well as code nested up to the parser's.
 pub fn run(input: &str) -> Result<(), String> {
+    thread::scope(|scope| {
+        let script = thread::Builder::new()
+            .stack_size(MAX_CALL_DEPTH * STACK_PER_CALL + MAX_NESTING * STACK_PER_NESTING)
+            .spawn_scoped(scope, || run_script(input))
+            .expect("Could not start a thread to run the script.");
+
+        script
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    })
+}
+
+fn run_script(input: &str) -> Result<(), String> {
     let mut scanner = Scanner::new(input);
-    let tokens = scanner
-        .scan_tokens()
-        .unwrap_or_else(|error| panic!("{}", error));
-    let mut parser = Parser::new(&tokens);
+    let tokens = scanner.scan_tokens().map_err(|error| error.to_string())?;
+    let mut parser = Parser::new(tokens);
     let statements = parser.parse().map_err(|error| error.to_string())?;
+
+    for warning in parser.warnings() {
+        println!("Warning: {}", warning);
+    }
+
     let mut interpreter = Interpreter::new();
 
-    interpreter.interpret_stmts(statements)?;
+    interpreter
+        .interpret_stmts(statements)
+        .map_err(|error| error.to_string())?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::run;
+
+    #[test]
+    fn should_report_invalid_escape_instead_of_panicking() {
+        let error = run("print \"a\\qb\";").unwrap_err();
+
+        assert!(error.starts_with("Invalid escape sequence in string."));
+        assert!(error.contains("line 1"));
+    }
+
+    #[test]
+    fn should_report_invalid_numbers_with_their_location() {
+        for source in ["0x;", "1e;", "1_;", "0b102;"] {
+            let error = run(&format!("print\n{}", source)).unwrap_err();
+
+            assert!(error.starts_with("Could not convert a string into a number."));
+            assert!(error.contains("line 2"), "{}", error);
+        }
+    }
+
+    #[test]
+    fn should_stop_runaway_recursion_with_a_catchable_error() {
+        let error = run("fun f(n) {\n return f(n + 1);\n}\nf(0);").unwrap_err();
+
+        assert!(error.starts_with("Stack overflow."), "{}", error);
+        assert!(error.contains("line 2"), "{}", error);
+
+        let caught = run(r#"
+            fun f(n) { return f(n + 1); }
+            var message = nil;
+            try { f(0); } catch (e) { message = e.message; }
+            if (message != "Stack overflow.") throw message;
+            fun depth(n) { return n == 0 ? 0 : 1 + depth(n - 1); }
+            if (depth(900) != 900) throw "depth";
+            "#);
+
+        assert!(caught.is_ok(), "{:?}", caught);
+    }
+
+    #[test]
+    fn should_report_deep_nesting_instead_of_overflowing() {
+        for (open, close) in [("(", ")"), ("[", "]"), ("-", ""), ("{", "}")] {
+            let nested = format!("{}1{}", open.repeat(20000), close.repeat(20000));
+            let error = run(&format!("print\n{};", nested)).unwrap_err();
+
+            assert!(error.contains("Code is nested too deeply."), "{}", error);
+            assert!(error.contains("line 2"), "{}", error);
+        }
+
+        let statements = format!("{}print 1;{}", "{".repeat(20000), "}".repeat(20000));
+
+        assert!(run(&statements)
+            .unwrap_err()
+            .contains("Code is nested too deeply."));
+
+        let allowed = format!("print {}1{};", "(".repeat(400), ")".repeat(400));
+
+        assert!(run(&allowed).is_ok());
+    }
+}
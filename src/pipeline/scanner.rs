@@ -1,8 +1,8 @@
-use std::{collections::HashMap, sync::Once};
+use std::{collections::HashMap, sync::OnceLock};
 
 use crate::{
     error::{CodeLocation, DbgDisplay},
-    types::{Lit, Tok, TokType},
+    types::{BigInt, Lit, Tok, TokType},
     util::string::{is_alpha, is_alphanumeric, is_digit, parse_string},
 };
 
@@ -17,6 +17,9 @@ pub struct Scanner {
     /// The current index of the character in the source code being evaluated. Atomically incremented on a per-character basis.
     current: usize,
     line: usize,
+    /// One entry per `${` interpolation currently open, holding how many unclosed `{` it contains.
+    /// When a `}` arrives with a count of zero, it closes the interpolation and the string resumes.
+    interpolations: Vec<usize>,
 }
 
 #[derive(Error, Debug)]
@@ -25,39 +28,45 @@ pub enum ScannerError {
     UnexpectedEof,
     #[error("Unterminated string. All strings must close. {0}.")]
     UnterminatedString(CodeLocation),
+    #[error("Invalid escape sequence in string. {0}.")]
+    InvalidEscape(CodeLocation),
     #[error("Could not convert a string into a number. {0}.")]
     InvalidNumber(CodeLocation),
     #[error("Unexpected token. {0}.")]
     UnknownToken(CodeLocation),
 }
 
-static mut KEYWORDS: Option<HashMap<&'static str, TokType>> = None;
-static INIT: Once = Once::new();
+static KEYWORDS: OnceLock<HashMap<&'static str, TokType>> = OnceLock::new();
 
 fn get_keywords() -> &'static HashMap<&'static str, TokType> {
-    unsafe {
-        INIT.call_once(|| {
-            KEYWORDS = Some(HashMap::from([
-                ("and", TokType::And),
-                ("class", TokType::Class),
-                ("else", TokType::Else),
-                ("false", TokType::False),
-                ("for", TokType::For),
-                ("fun", TokType::Fun),
-                ("if", TokType::If),
-                ("nil", TokType::Nil),
-                ("or", TokType::Or),
-                ("print", TokType::Print),
-                ("return", TokType::Return),
-                ("super", TokType::Super),
-                ("this", TokType::This),
-                ("true", TokType::True),
-                ("var", TokType::Var),
-                ("while", TokType::While),
-            ]));
-        });
-        KEYWORDS.as_ref().unwrap()
-    }
+    KEYWORDS.get_or_init(|| {
+        HashMap::from([
+            ("and", TokType::And),
+            ("break", TokType::Break),
+            ("catch", TokType::Catch),
+            ("class", TokType::Class),
+            ("continue", TokType::Continue),
+            ("else", TokType::Else),
+            ("enum", TokType::Enum),
+            ("false", TokType::False),
+            ("finally", TokType::Finally),
+            ("for", TokType::For),
+            ("fun", TokType::Fun),
+            ("if", TokType::If),
+            ("match", TokType::Match),
+            ("nil", TokType::Nil),
+            ("or", TokType::Or),
+            ("print", TokType::Print),
+            ("return", TokType::Return),
+            ("super", TokType::Super),
+            ("this", TokType::This),
+            ("throw", TokType::Throw),
+            ("true", TokType::True),
+            ("try", TokType::Try),
+            ("var", TokType::Var),
+            ("while", TokType::While),
+        ])
+    })
 }
 
 impl Scanner {
@@ -68,6 +77,7 @@ impl Scanner {
             current: 0,
             start: 0,
             line: 1,
+            interpolations: vec![],
         }
     }
 
@@ -81,6 +91,13 @@ impl Scanner {
             self.scan_token()?;
         }
 
+        if !self.interpolations.is_empty() {
+            return Err(ScannerError::UnterminatedString(CodeLocation {
+                line: self.line,
+                display: DbgDisplay::from(&"${".to_string()),
+            }));
+        }
+
         self.tokens.push(Tok {
             token_type: TokType::Eof,
             lexeme: "".to_string(),
@@ -98,20 +115,107 @@ impl Scanner {
         match character {
             '(' => Ok(self.add_token(TokType::LeftParen, None)),
             ')' => Ok(self.add_token(TokType::RightParen, None)),
-            '{' => Ok(self.add_token(TokType::LeftBrace, None)),
-            '}' => Ok(self.add_token(TokType::RightBrace, None)),
+            '{' => {
+                if let Some(depth) = self.interpolations.last_mut() {
+                    *depth += 1;
+                }
+
+                Ok(self.add_token(TokType::LeftBrace, None))
+            }
+            '}' => match self.interpolations.last_mut() {
+                Some(0) => {
+                    self.interpolations.pop();
+
+                    Ok(self.scan_string()?)
+                }
+                Some(depth) => {
+                    *depth -= 1;
+
+                    Ok(self.add_token(TokType::RightBrace, None))
+                }
+                None => Ok(self.add_token(TokType::RightBrace, None)),
+            },
+            '[' => Ok(self.add_token(TokType::LeftBracket, None)),
+            ']' => Ok(self.add_token(TokType::RightBracket, None)),
+            ':' => Ok(self.add_token(TokType::Colon, None)),
             ',' => Ok(self.add_token(TokType::Comma, None)),
-            '.' => Ok(self.add_token(TokType::Dot, None)),
-            '-' => Ok(self.add_token(TokType::Minus, None)),
-            '+' => Ok(self.add_token(TokType::Plus, None)),
+            '.' => {
+                let token_type = if !self.consume('.') {
+                    TokType::Dot
+                } else if self.consume('=') {
+                    TokType::DotDotEqual
+                } else {
+                    TokType::DotDot
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
+            '-' => {
+                let token_type = if self.consume('-') {
+                    TokType::MinusMinus
+                } else if self.consume('=') {
+                    TokType::MinusEqual
+                } else {
+                    TokType::Minus
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
+            '+' => {
+                let token_type = if self.consume('+') {
+                    TokType::PlusPlus
+                } else if self.consume('=') {
+                    TokType::PlusEqual
+                } else {
+                    TokType::Plus
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
             ';' => Ok(self.add_token(TokType::Semicolon, None)),
-            '*' => Ok(self.add_token(TokType::Star, None)),
+            '*' => {
+                let token_type = if self.consume('*') {
+                    TokType::StarStar
+                } else if self.consume('=') {
+                    TokType::StarEqual
+                } else {
+                    TokType::Star
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
+            '%' => {
+                let token_type = if self.consume('=') {
+                    TokType::PercentEqual
+                } else {
+                    TokType::Percent
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
+            '&' => Ok(self.add_token(TokType::Ampersand, None)),
+            '|' => Ok(self.add_token(TokType::Pipe, None)),
+            '^' => Ok(self.add_token(TokType::Caret, None)),
+            '~' => Ok(self.add_token(TokType::Tilde, None)),
+            '?' => {
+                let token_type = if self.consume('?') {
+                    TokType::QuestionQuestion
+                } else if self.consume('.') {
+                    TokType::QuestionDot
+                } else {
+                    TokType::Question
+                };
+
+                Ok(self.add_token(token_type, None))
+            }
             '"' => Ok(self.scan_string()?),
             '/' => {
                 if self.consume('/') {
                     Ok(self.skip_comment())
                 } else if self.consume('*') {
                     Ok(self.skip_block_comment())
+                } else if self.consume('=') {
+                    Ok(self.add_token(TokType::SlashEqual, None))
                 } else {
                     Ok(self.add_token(TokType::Slash, None))
                 }
@@ -120,7 +224,7 @@ impl Scanner {
                 let token_type = if self.consume('=') {
                     TokType::BangEqual
                 } else {
-                    TokType::Equal
+                    TokType::Bang
                 };
 
                 Ok(self.add_token(token_type, None))
@@ -128,6 +232,8 @@ impl Scanner {
             '=' => {
                 let token_type = if self.consume('=') {
                     TokType::EqualEqual
+                } else if self.consume('>') {
+                    TokType::EqualGreater
                 } else {
                     TokType::Equal
                 };
@@ -137,6 +243,8 @@ impl Scanner {
             '<' => {
                 let token_type = if self.consume('=') {
                     TokType::LessEqual
+                } else if self.consume('<') {
+                    TokType::LessLess
                 } else {
                     TokType::Less
                 };
@@ -146,6 +254,8 @@ impl Scanner {
             '>' => {
                 let token_type = if self.consume('=') {
                     TokType::GreaterEqual
+                } else if self.consume('>') {
+                    TokType::GreaterGreater
                 } else {
                     TokType::Greater
                 };
@@ -201,56 +311,190 @@ impl Scanner {
     /// This method is usually called when a quotation mark has been identified int the source code.
     /// This method will collate all characters in the source code up until the point a closing quotation mark is found.
     /// Will return that collection of characters as a string token type.
+    ///
+    /// It is also called when the `}` closing an interpolation is found, to scan the rest of the string.
+    /// If a `${` is found first, the characters so far become an interpolation token instead.
+    /// The tokens of the embedded expression are then scanned as normal until its closing `}`.
+    ///
+    /// Escape sequences are decoded into the token's literal, while its lexeme keeps the source as written.
     fn scan_string(&mut self) -> Result<TokType, ScannerError> {
+        let mut value = String::new();
+
         while self.peek() != '"' && !self.is_at_end() {
+            if self.peek() == '$' && self.peek_at(self.current + 1) == '{' {
+                self.advance();
+                self.advance();
+                self.interpolations.push(0);
+
+                let added_token = self.add_token(TokType::Interpolation, Some(Lit::String(value)));
+
+                return Ok(added_token);
+            }
+
+            if self.peek() == '\\' {
+                self.advance();
+                value.push(self.scan_escape()?);
+
+                continue;
+            }
+
             if self.peek() == '\n' {
                 self.line += 1;
             }
 
-            self.advance();
+            value.extend(self.advance());
         }
 
         if self.is_at_end() {
             return Err(ScannerError::UnterminatedString(CodeLocation {
                 line: self.line,
-                display: DbgDisplay::from(self.tokens.last().unwrap()),
+                display: DbgDisplay::from(&self.get_source_slice(self.start, self.current)),
             }));
         }
 
         // Consume the last " char
         self.advance();
 
-        let value = self.get_source_slice(self.start + 1, self.current - 1); // +1 and -1 to exclude the quotation marks.
         let added_token = self.add_token(TokType::String, Some(Lit::String(value)));
 
         Ok(added_token)
     }
 
+    /// Decodes the escape sequence following a backslash, which has already been consumed.
+    /// Supports `\n`, `\t`, `\r`, `\\`, `\"`, `\0`, `\$` and code points written as `\u{1F600}`.
+    fn scan_escape(&mut self) -> Result<char, ScannerError> {
+        let escape_start = self.current - 1;
+
+        let invalid_escape = |scanner: &Self| {
+            ScannerError::InvalidEscape(CodeLocation {
+                line: scanner.line,
+                display: DbgDisplay::from(&scanner.get_source_slice(escape_start, scanner.current)),
+            })
+        };
+
+        let character = match self.advance() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('$') => '$',
+            Some('u') => {
+                if !self.consume('{') {
+                    return Err(invalid_escape(self));
+                }
+
+                let digits_start = self.current;
+
+                while self.peek().is_ascii_hexdigit() {
+                    self.advance();
+                }
+
+                let digits = self.get_source_slice(digits_start, self.current);
+
+                if !self.consume('}') || digits.is_empty() || digits.len() > 6 {
+                    return Err(invalid_escape(self));
+                }
+
+                u32::from_str_radix(&digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| invalid_escape(self))?
+            }
+            _ => return Err(invalid_escape(self)),
+        };
+
+        Ok(character)
+    }
+
+    /// Scans decimal numbers like `1_000`, `1.5` and `6.02e23`, and integers with a
+    /// `0x`, `0b` or `0o` radix prefix. The first digit has already been consumed.
     fn scan_number(&mut self) -> Result<TokType, ScannerError> {
-        while is_digit(self.peek()) {
-            self.advance();
-        }
+        let radix = match (self.peek_at(self.start), self.peek()) {
+            ('0', 'x' | 'X') => 16,
+            ('0', 'b' | 'B') => 2,
+            ('0', 'o' | 'O') => 8,
+            _ => 10,
+        };
 
-        let peek_next = self.peek_at(self.current + 1);
+        let number = if radix == 10 {
+            // Rescan from the first digit so that separators are checked across the whole integer part
+            self.current = self.start;
 
-        if self.peek() == '.' && is_digit(peek_next) {
-            self.advance();
+            let mut valid = self.scan_digits(10);
+            let mut is_float = false;
+
+            if self.peek() == '.' && is_digit(self.peek_at(self.current + 1)) {
+                self.advance();
+                valid &= self.scan_digits(10);
+                is_float = true;
+            }
 
-            while is_digit(self.peek()) {
+            if matches!(self.peek(), 'e' | 'E') {
                 self.advance();
+                if !self.consume('+') {
+                    self.consume('-');
+                }
+                valid &= self.scan_digits(10);
+                is_float = true;
             }
+
+            let value = self
+                .get_source_slice(self.start, self.current)
+                .replace('_', "");
+
+            // Without a fraction or exponent the literal is an exact integer
+            if is_float {
+                parse_string(&value).map(Lit::Number)
+            } else {
+                BigInt::parse(&value, 10).map(Lit::from)
+            }
+            .filter(|_| valid)
+        } else {
+            // Skip the radix prefix
+            self.advance();
+
+            let valid = self.scan_digits(radix);
+            let digits = self.get_source_slice(self.start + 2, self.current);
+
+            BigInt::parse(&digits.replace('_', ""), radix)
+                .filter(|_| valid)
+                .map(Lit::from)
+        };
+
+        // A number running straight into letters or separators, like `0b12` or `1e`, is malformed
+        let runs_on = is_alphanumeric(self.peek());
+
+        match number {
+            Some(number) if !runs_on => Ok(self.add_token(TokType::Number, Some(number))),
+            _ => Err(self.invalid_number()),
         }
+    }
 
-        let value = self.get_source_slice(self.start, self.current);
+    /// Consumes digits of the given radix, which may be separated by `_`.
+    /// Returns false if there were no digits or the last character was a `_`.
+    fn scan_digits(&mut self, radix: u32) -> bool {
+        let mut ends_with_digit = false;
 
-        let number = parse_string(&value).ok_or(ScannerError::InvalidNumber(CodeLocation {
-            line: self.line,
-            display: DbgDisplay::from(&value),
-        }))?;
+        while self.peek().is_digit(radix) || self.peek() == '_' {
+            ends_with_digit = self.peek() != '_';
+            self.advance();
+        }
 
-        let added_token = self.add_token(TokType::Number, Some(Lit::Number(number)));
+        ends_with_digit
+    }
 
-        Ok(added_token)
+    /// Consumes the rest of a malformed number so the error shows all of it.
+    fn invalid_number(&mut self) -> ScannerError {
+        while is_alphanumeric(self.peek()) {
+            self.advance();
+        }
+
+        ScannerError::InvalidNumber(CodeLocation {
+            line: self.line,
+            display: DbgDisplay::from(&self.get_source_slice(self.start, self.current)),
+        })
     }
 
     fn scan_ident(&mut self) -> Result<TokType, ScannerError> {
@@ -291,7 +535,7 @@ impl Scanner {
 
     // Return a bool to identify if the scanner's current position is at the end of the file.
     fn is_at_end(&self) -> bool {
-        self.current >= self.source.len().try_into().unwrap() // usize always converts to u64, unwrap here is safe.
+        self.current >= self.source.len()
     }
 
     /// Return at the char found at self.current. Does not advance self.current. \0 if is at EOF.
@@ -323,7 +567,7 @@ impl Scanner {
     }
 
     /// The slice in the scanner is stored as a vector of chars. This will concatenate a slice and return a string.
-    fn get_source_slice<'a>(&self, start: usize, end: usize) -> String {
+    fn get_source_slice(&self, start: usize, end: usize) -> String {
         self.source
             .get(start..end)
             .expect("Critical error in scanning source code. Attempted to extract a slice of source with an out of bounds index.")
@@ -337,7 +581,7 @@ impl Scanner {
         let lexeme = self.get_source_slice(start, current);
 
         let token = Tok {
-            token_type: token_type.clone(),
+            token_type,
             lexeme,
             literal: literal_type,
             line: self.line,
@@ -353,6 +597,7 @@ impl Scanner {
 mod tests {
     use crate::{
         constants::{CRLF, LF},
+        error::CodeLocation,
         pipeline::scanner::ScannerError,
         types::{Lit, Tok},
     };
@@ -436,6 +681,130 @@ mod tests {
         assert_eq!(scanner.line, 1);
     }
 
+    #[test]
+    fn should_match_bang_without_equal() {
+        let source = "!true";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        assert_eq!(scanner.tokens[0].token_type, TokType::Bang);
+    }
+
+    #[test]
+    fn should_match_arithmetic_and_bitwise_operators() {
+        let source = "% ** * & | ^ ~ << <= < >> >= >";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        let token_types: Vec<TokType> = scanner.tokens.iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            [
+                TokType::Percent,
+                TokType::StarStar,
+                TokType::Star,
+                TokType::Ampersand,
+                TokType::Pipe,
+                TokType::Caret,
+                TokType::Tilde,
+                TokType::LessLess,
+                TokType::LessEqual,
+                TokType::Less,
+                TokType::GreaterGreater,
+                TokType::GreaterEqual,
+                TokType::Greater,
+                TokType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_match_update_operators() {
+        let source = "x++ y-- += -= *= /= %= - +";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        let token_types: Vec<TokType> = scanner.tokens.iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            [
+                TokType::Identifier,
+                TokType::PlusPlus,
+                TokType::Identifier,
+                TokType::MinusMinus,
+                TokType::PlusEqual,
+                TokType::MinusEqual,
+                TokType::StarEqual,
+                TokType::SlashEqual,
+                TokType::PercentEqual,
+                TokType::Minus,
+                TokType::Plus,
+                TokType::Eof,
+            ]
+        );
+    }
+
+    #[test]
+    fn should_match_question_operators() {
+        let source = "a ? b : c ?? d?.e";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        assert_eq!(scanner.tokens[1].token_type, TokType::Question);
+        assert_eq!(scanner.tokens[3].token_type, TokType::Colon);
+        assert_eq!(scanner.tokens[5].token_type, TokType::QuestionQuestion);
+        assert_eq!(scanner.tokens[7].token_type, TokType::QuestionDot);
+    }
+
+    #[test]
+    fn should_match_range_and_arrow_operators() {
+        let source = "1..10 1..=my_var => _";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        let token_types: Vec<TokType> = scanner
+            .tokens
+            .iter()
+            .map(|token| token.token_type)
+            .collect();
+
+        assert_eq!(
+            token_types,
+            [
+                TokType::Number,
+                TokType::DotDot,
+                TokType::Number,
+                TokType::Number,
+                TokType::DotDotEqual,
+                TokType::Identifier,
+                TokType::EqualGreater,
+                TokType::Identifier,
+                TokType::Eof,
+            ]
+        );
+        assert_eq!(scanner.tokens[5].lexeme, "my_var");
+    }
+
+    #[test]
+    fn should_match_brackets() {
+        let source = "[1, [2]]";
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        assert_eq!(scanner.tokens[0].token_type, TokType::LeftBracket);
+        assert_eq!(scanner.tokens[3].token_type, TokType::LeftBracket);
+        assert_eq!(scanner.tokens[5].token_type, TokType::RightBracket);
+        assert_eq!(scanner.tokens[6].token_type, TokType::RightBracket);
+    }
+
     #[test]
     fn should_match_string_literal() {
         let source = "\"hey\"";
@@ -475,6 +844,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn should_split_interpolated_string() {
+        let source = r#""Hi ${name}, ${ {"k": "${1 + 1}"}["k"] }!""#;
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        let token_types: Vec<TokType> = scanner.tokens.iter().map(|t| t.token_type).collect();
+
+        assert_eq!(
+            token_types,
+            vec![
+                TokType::Interpolation,
+                TokType::Identifier,
+                TokType::Interpolation,
+                TokType::LeftBrace,
+                TokType::String,
+                TokType::Colon,
+                TokType::Interpolation,
+                TokType::Number,
+                TokType::Plus,
+                TokType::Number,
+                TokType::String,
+                TokType::RightBrace,
+                TokType::LeftBracket,
+                TokType::String,
+                TokType::RightBracket,
+                TokType::String,
+                TokType::Eof,
+            ]
+        );
+        assert_eq!(
+            scanner.tokens[0].literal,
+            Some(Lit::String("Hi ".to_string()))
+        );
+        assert_eq!(
+            scanner.tokens[2].literal,
+            Some(Lit::String(", ".to_string()))
+        );
+        assert_eq!(
+            scanner.tokens[15].literal,
+            Some(Lit::String("!".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_error_on_unterminated_interpolation() {
+        let source = r#""Hi ${name"#;
+        let mut scanner = Scanner::new(source);
+
+        let result = scanner.scan_tokens();
+
+        assert!(matches!(result, Err(ScannerError::UnterminatedString(_))));
+    }
+
+    #[test]
+    fn should_decode_escapes_and_keep_lexeme() {
+        let source = r#""a\n\t\r\\\"\0\$\u{1F600}b""#;
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        assert_eq!(
+            scanner.tokens[0].literal,
+            Some(Lit::String("a\n\t\r\\\"\0$\u{1F600}b".to_string()))
+        );
+        assert_eq!(scanner.tokens[0].lexeme, source);
+    }
+
+    #[test]
+    fn should_not_interpolate_escaped_dollar() {
+        let source = r#""\${name}""#;
+        let mut scanner = Scanner::new(source);
+
+        scanner.scan_tokens().unwrap();
+
+        assert_eq!(scanner.tokens[0].token_type, TokType::String);
+        assert_eq!(
+            scanner.tokens[0].literal,
+            Some(Lit::String("${name}".to_string()))
+        );
+    }
+
+    #[test]
+    fn should_error_on_invalid_escapes() {
+        for source in [r#""\q""#, r#""\u{}""#, r#""\u{110000}""#, r#""\u1F600""#] {
+            let mut scanner = Scanner::new(source);
+
+            let result = scanner.scan_tokens();
+
+            assert!(
+                matches!(result, Err(ScannerError::InvalidEscape(_))),
+                "{source} should be an invalid escape"
+            );
+        }
+    }
+
+    #[test]
+    fn should_report_line_of_invalid_escape() {
+        let source = "\n\n\"bad \\x\"";
+        let mut scanner = Scanner::new(source);
+
+        let result = scanner.scan_tokens();
+
+        assert!(matches!(
+            result,
+            Err(ScannerError::InvalidEscape(CodeLocation { line: 3, .. }))
+        ));
+    }
+
     #[test]
     fn should_match_number() {
         let source = "100";
@@ -482,7 +961,7 @@ mod tests {
 
         scanner.scan_tokens().unwrap();
 
-        assert_eq!(scanner.tokens[0].literal, Some(Lit::Number(100.0)));
+        assert!(matches!(scanner.tokens[0].literal, Some(Lit::Integer(100))));
     }
 
     #[test]
@@ -492,7 +971,82 @@ mod tests {
 
         scanner.scan_tokens().unwrap();
 
-        assert_eq!(scanner.tokens[0].literal, Some(Lit::Number(10.1)));
+        assert!(matches!(scanner.tokens[0].literal, Some(Lit::Number(n)) if n == 10.1));
+    }
+
+    #[test]
+    fn should_match_radix_scientific_and_separated_numbers() {
+        let cases = [
+            ("0xFF", Lit::Integer(255)),
+            ("0XfF", Lit::Integer(255)),
+            ("0b1010", Lit::Integer(10)),
+            ("0o755", Lit::Integer(493)),
+            ("0b_1111_0000", Lit::Integer(240)),
+            ("6.02e23", Lit::Number(6.02e23)),
+            ("1e-9", Lit::Number(1e-9)),
+            ("2.5E+3", Lit::Number(2500.0)),
+            ("1e3", Lit::Number(1000.0)),
+            ("1_000_000", Lit::Integer(1_000_000)),
+            ("1_000.000_5", Lit::Number(1000.0005)),
+        ];
+
+        for (source, expected) in cases {
+            let mut scanner = Scanner::new(source);
+
+            scanner.scan_tokens().unwrap();
+
+            assert_eq!(scanner.tokens.len(), 2, "{source} should be one token");
+            // Compare the debug form too, since `1 == 1.0` for Lit
+            assert_eq!(
+                format!("{:?}", scanner.tokens[0].literal),
+                format!("{:?}", Some(expected)),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn should_match_integers_too_big_for_i64() {
+        for (source, expected) in [
+            ("9223372036854775808", "9223372036854775808"),
+            ("0xFFFF_FFFF_FFFF_FFFF_FFFF", "1208925819614629174706175"),
+        ] {
+            let mut scanner = Scanner::new(source);
+
+            scanner.scan_tokens().unwrap();
+
+            assert!(matches!(
+                &scanner.tokens[0].literal,
+                Some(literal @ Lit::BigInteger(_)) if literal.to_string() == expected
+            ));
+        }
+    }
+
+    #[test]
+    fn should_error_on_malformed_numbers() {
+        for source in [
+            "0x", "0b", "0b102", "1e", "1e+", "1_", "1__", "1_.5", "1.5_", "2e5_", "1e+-5", "0o8",
+            "12abc",
+        ] {
+            let mut scanner = Scanner::new(source);
+
+            let result = scanner.scan_tokens();
+
+            assert!(
+                matches!(result, Err(ScannerError::InvalidNumber(_))),
+                "{source} should be an invalid number"
+            );
+        }
+    }
+
+    #[test]
+    fn should_report_whole_malformed_number() {
+        let source = "\nvar x = 0xZZ;";
+        let mut scanner = Scanner::new(source);
+
+        let error = scanner.scan_tokens().unwrap_err().to_string();
+
+        assert!(error.contains("At 0xZZ, line 2"));
     }
 
     #[test]
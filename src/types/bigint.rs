@@ -0,0 +1,666 @@
+use std::{
+    cmp::Ordering,
+    ops::{Add, BitAnd, BitOr, BitXor, Mul, Neg, Shl, Shr, Sub},
+};
+
+/// An arbitrary-precision integer, used once integer arithmetic overflows an `i64`.
+///
+/// The magnitude is stored as base 2^32 limbs, least significant first, with no trailing zero limbs.
+/// Zero has an empty magnitude and is never negative, so derived equality and hashing are by value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    magnitude: Vec<u32>,
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mut magnitude: Vec<u32>) -> Self {
+        while magnitude.last() == Some(&0) {
+            magnitude.pop();
+        }
+
+        Self {
+            negative: negative && !magnitude.is_empty(),
+            magnitude,
+        }
+    }
+
+    /// Parses unsigned digits in the given radix. Returns None if there are no digits or one is invalid.
+    pub fn parse(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut magnitude = Vec::new();
+
+        for c in digits.chars() {
+            multiply_add_small(&mut magnitude, radix, c.to_digit(radix)?);
+        }
+
+        Some(Self::from_parts(false, magnitude))
+    }
+
+    /// Converts a whole, finite float exactly. Returns None for anything else.
+    pub fn from_f64(float: f64) -> Option<Self> {
+        if !float.is_finite() || float.fract() != 0.0 {
+            return None;
+        }
+
+        let bits = float.to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64;
+
+        // Subnormals are all fractions, so a whole subnormal can only be zero
+        if exponent == 0 {
+            return Some(Self::from(0));
+        }
+
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        let shift = exponent - 1075;
+
+        let magnitude = if shift >= 0 {
+            shift_left(&split_u64(mantissa), shift as usize)
+        } else {
+            // The float is whole, so the bits shifted out are all zero
+            split_u64(mantissa >> -shift)
+        };
+
+        Some(Self::from_parts(float < 0.0, magnitude))
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.magnitude.len() > 2 {
+            return None;
+        }
+
+        let value = self
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0_u64, |value, limb| (value << 32) | *limb as u64);
+
+        if self.negative {
+            0_i64.checked_sub_unsigned(value)
+        } else {
+            i64::try_from(value).ok()
+        }
+    }
+
+    /// The nearest float, or an infinity if the value is too big.
+    pub fn to_f64(&self) -> f64 {
+        // Keep the top 64 bits and fold everything below them into the lowest one, so that
+        // converting to a float rounds once rather than once per limb
+        let shift = self.bit_length().saturating_sub(64) as usize;
+        let top = &BigInt::from_parts(false, self.magnitude.clone()) >> shift;
+
+        let lost_bits = self.magnitude[..shift / 32].iter().any(|limb| *limb != 0)
+            || self
+                .magnitude
+                .get(shift / 32)
+                .is_some_and(|limb| limb & ((1 << (shift % 32)) - 1) != 0);
+
+        let top = top
+            .magnitude
+            .iter()
+            .rev()
+            .fold(0_u64, |value, limb| (value << 32) | *limb as u64);
+
+        // Scaling by a power of two is exact, and overflows to infinity when the value is too big
+        let magnitude =
+            (top | lost_bits as u64) as f64 * 2_f64.powi(shift.min(i32::MAX as usize) as i32);
+
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.magnitude.first().is_some_and(|limb| limb & 1 == 1)
+    }
+
+    /// The number of bits in the magnitude, ignoring the sign.
+    pub fn bit_length(&self) -> u64 {
+        match self.magnitude.last() {
+            Some(top) => self.magnitude.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    /// Truncating division, so the remainder takes the sign of the dividend.
+    /// Returns None when dividing by zero.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        if divisor.is_zero() {
+            return None;
+        }
+
+        let (quotient, remainder) = div_rem_magnitude(&self.magnitude, &divisor.magnitude);
+
+        Some((
+            Self::from_parts(self.negative != divisor.negative, quotient),
+            Self::from_parts(self.negative, remainder),
+        ))
+    }
+
+    pub fn pow(&self, mut exponent: u64) -> Self {
+        let mut result = Self::from(1);
+        let mut base = self.clone();
+
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = &result * &base;
+            }
+
+            exponent >>= 1;
+
+            if exponent > 0 {
+                base = &base * &base;
+            }
+        }
+
+        result
+    }
+
+    /// Applies a bitwise operation limb by limb, treating both values as infinitely sign-extended
+    /// two's complement, which is how `i64` behaves.
+    fn bitwise(&self, other: &Self, operation: impl Fn(u32, u32) -> u32) -> Self {
+        // One extra limb so the top limb only holds sign bits
+        let len = self.magnitude.len().max(other.magnitude.len()) + 1;
+
+        let mut limbs: Vec<u32> = self
+            .twos_complement(len)
+            .iter()
+            .zip(other.twos_complement(len))
+            .map(|(l, r)| operation(*l, r))
+            .collect();
+
+        let negative = limbs[len - 1] >> 31 == 1;
+
+        if negative {
+            negate_limbs(&mut limbs);
+        }
+
+        Self::from_parts(negative, limbs)
+    }
+
+    fn twos_complement(&self, len: usize) -> Vec<u32> {
+        let mut limbs = self.magnitude.clone();
+        limbs.resize(len, 0);
+
+        if self.negative {
+            negate_limbs(&mut limbs);
+        }
+
+        limbs
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(value: i64) -> Self {
+        Self::from_parts(value < 0, split_u64(value.unsigned_abs()))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.magnitude, &other.magnitude),
+            (true, true) => compare_magnitude(&other.magnitude, &self.magnitude),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.magnitude.clone())
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(
+                self.negative,
+                add_magnitude(&self.magnitude, &other.magnitude),
+            );
+        }
+
+        // Opposite signs, so subtract the smaller magnitude from the larger and keep the larger's sign
+        match compare_magnitude(&self.magnitude, &other.magnitude) {
+            Ordering::Less => BigInt::from_parts(
+                other.negative,
+                sub_magnitude(&other.magnitude, &self.magnitude),
+            ),
+            _ => BigInt::from_parts(
+                self.negative,
+                sub_magnitude(&self.magnitude, &other.magnitude),
+            ),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.magnitude, &other.magnitude),
+        )
+    }
+}
+
+impl BitAnd for &BigInt {
+    type Output = BigInt;
+
+    fn bitand(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |l, r| l & r)
+    }
+}
+
+impl BitOr for &BigInt {
+    type Output = BigInt;
+
+    fn bitor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |l, r| l | r)
+    }
+}
+
+impl BitXor for &BigInt {
+    type Output = BigInt;
+
+    fn bitxor(self, other: &BigInt) -> BigInt {
+        self.bitwise(other, |l, r| l ^ r)
+    }
+}
+
+impl Shl<usize> for &BigInt {
+    type Output = BigInt;
+
+    fn shl(self, bits: usize) -> BigInt {
+        BigInt::from_parts(self.negative, shift_left(&self.magnitude, bits))
+    }
+}
+
+impl Shr<usize> for &BigInt {
+    type Output = BigInt;
+
+    /// Rounds towards negative infinity like `i64`'s `>>`, so `-1 >> 1` is still `-1`.
+    fn shr(self, bits: usize) -> BigInt {
+        let skipped = bits / 32;
+        let offset = bits % 32;
+
+        if skipped >= self.magnitude.len() {
+            return BigInt::from(if self.negative { -1 } else { 0 });
+        }
+
+        let lost_bits = self.magnitude[..skipped].iter().any(|limb| *limb != 0)
+            || self.magnitude[skipped] & ((1 << offset) - 1) != 0;
+
+        let kept = &self.magnitude[skipped..];
+        let magnitude = (0..kept.len())
+            .map(|i| match (offset, kept.get(i + 1)) {
+                (0, _) | (_, None) => kept[i] >> offset,
+                (_, Some(next)) => (kept[i] >> offset) | (next << (32 - offset)),
+            })
+            .collect();
+
+        let shifted = BigInt::from_parts(self.negative, magnitude);
+
+        if self.negative && lost_bits {
+            &shifted - &BigInt::from(1)
+        } else {
+            shifted
+        }
+    }
+}
+
+impl std::fmt::Display for BigInt {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+
+        // Peel off nine decimal digits at a time, least significant first
+        let mut magnitude = self.magnitude.clone();
+        let mut chunks = Vec::new();
+
+        while !magnitude.is_empty() {
+            chunks.push(div_rem_small(&mut magnitude, 1_000_000_000));
+        }
+
+        if self.negative {
+            write!(f, "-")?;
+        }
+
+        let mut chunks = chunks.iter().rev();
+
+        if let Some(first) = chunks.next() {
+            write!(f, "{}", first)?;
+        }
+
+        for chunk in chunks {
+            write!(f, "{:09}", chunk)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn split_u64(value: u64) -> Vec<u32> {
+    let mut magnitude = vec![value as u32, (value >> 32) as u32];
+
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+
+    magnitude
+}
+
+/// Negates two's complement limbs in place, by inverting them and adding one.
+fn negate_limbs(limbs: &mut [u32]) {
+    let mut carry = true;
+
+    for limb in limbs {
+        let (value, overflowed) = (!*limb).overflowing_add(carry as u32);
+        *limb = value;
+        carry = overflowed;
+    }
+}
+
+fn compare_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(left.len().max(right.len()) + 1);
+    let mut carry = 0_u64;
+
+    for i in 0..left.len().max(right.len()) {
+        let sum = *left.get(i).unwrap_or(&0) as u64 + *right.get(i).unwrap_or(&0) as u64 + carry;
+        result.push(sum as u32);
+        carry = sum >> 32;
+    }
+
+    if carry > 0 {
+        result.push(carry as u32);
+    }
+
+    result
+}
+
+/// Subtracts `right` from `left`, which must be at least as large.
+fn sub_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut result = Vec::with_capacity(left.len());
+    let mut borrow = 0_i64;
+
+    for (i, limb) in left.iter().enumerate() {
+        let mut difference = *limb as i64 - *right.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+
+        if difference < 0 {
+            difference += 1 << 32;
+            borrow = 1;
+        }
+
+        result.push(difference as u32);
+    }
+
+    result
+}
+
+fn mul_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut result = vec![0_u32; left.len() + right.len()];
+
+    for (i, l) in left.iter().enumerate() {
+        let mut carry = 0_u64;
+
+        for (j, r) in right.iter().enumerate() {
+            let product = *l as u64 * *r as u64 + result[i + j] as u64 + carry;
+            result[i + j] = product as u32;
+            carry = product >> 32;
+        }
+
+        result[i + right.len()] = carry as u32;
+    }
+
+    result
+}
+
+fn multiply_add_small(magnitude: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+
+    for limb in magnitude.iter_mut() {
+        let value = *limb as u64 * factor as u64 + carry;
+        *limb = value as u32;
+        carry = value >> 32;
+    }
+
+    if carry > 0 {
+        magnitude.push(carry as u32);
+    }
+}
+
+/// Divides the magnitude in place and returns the remainder.
+fn div_rem_small(magnitude: &mut Vec<u32>, divisor: u32) -> u32 {
+    let mut remainder = 0_u64;
+
+    for limb in magnitude.iter_mut().rev() {
+        let value = (remainder << 32) | *limb as u64;
+        *limb = (value / divisor as u64) as u32;
+        remainder = value % divisor as u64;
+    }
+
+    while magnitude.last() == Some(&0) {
+        magnitude.pop();
+    }
+
+    remainder as u32
+}
+
+fn shift_left(magnitude: &[u32], bits: usize) -> Vec<u32> {
+    let mut result = vec![0_u32; bits / 32];
+    let offset = bits % 32;
+
+    if offset == 0 {
+        result.extend_from_slice(magnitude);
+        return result;
+    }
+
+    let mut carry = 0_u32;
+
+    for limb in magnitude {
+        result.push((limb << offset) | carry);
+        carry = limb >> (32 - offset);
+    }
+
+    if carry > 0 {
+        result.push(carry);
+    }
+
+    result
+}
+
+/// Schoolbook binary long division. Slow for huge divisors, but scripts rarely go there.
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if let [divisor] = divisor {
+        let mut quotient = dividend.to_vec();
+        let remainder = div_rem_small(&mut quotient, *divisor);
+        return (quotient, split_u64(remainder as u64));
+    }
+
+    let mut quotient = vec![0_u32; dividend.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+
+    for i in (0..dividend.len() * 32).rev() {
+        remainder = shift_left(&remainder, 1);
+
+        if (dividend[i / 32] >> (i % 32)) & 1 == 1 {
+            match remainder.first_mut() {
+                Some(low) => *low |= 1,
+                None => remainder.push(1),
+            }
+        }
+
+        if compare_magnitude(&remainder, divisor) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, divisor);
+
+            while remainder.last() == Some(&0) {
+                remainder.pop();
+            }
+
+            quotient[i / 32] |= 1 << (i % 32);
+        }
+    }
+
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigInt;
+
+    fn big(digits: &str) -> BigInt {
+        match digits.strip_prefix('-') {
+            Some(digits) => -&BigInt::parse(digits, 10).unwrap(),
+            None => BigInt::parse(digits, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn should_print_what_it_parses() {
+        for digits in [
+            "0",
+            "1",
+            "-1",
+            "1000000000",
+            "123456789012345678901234567890",
+        ] {
+            assert_eq!(big(digits).to_string(), digits);
+        }
+    }
+
+    #[test]
+    fn should_round_to_the_nearest_float_once() {
+        assert_eq!(
+            big("640689712370377751198527176054077677").to_f64(),
+            6.406897123703778e35
+        );
+        assert_eq!(
+            big("-18446744073709551617").to_f64(),
+            -18446744073709551616.0
+        );
+        assert_eq!(big("9007199254740993").to_f64(), 9007199254740992.0);
+        assert_eq!(big("18446744073709553665").to_f64(), 18446744073709555712.0);
+        assert_eq!(
+            big("340282366920938463463374607431768211457").to_f64(),
+            340282366920938463463374607431768211456.0
+        );
+        assert_eq!((&big("1") << 1100).to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn should_do_exact_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        assert_eq!(BigInt::from(2).pow(200).to_string().len(), 61);
+    }
+
+    #[test]
+    fn should_divide_truncating_towards_zero() {
+        let (quotient, remainder) = big("-100000000000000000000007")
+            .div_rem(&big("10000000000000000000000"))
+            .unwrap();
+
+        assert_eq!(quotient.to_string(), "-10");
+        assert_eq!(remainder.to_string(), "-7");
+        assert!(big("1").div_rem(&big("0")).is_none());
+    }
+
+    #[test]
+    fn should_convert_at_i64_and_f64_edges() {
+        assert_eq!(big("-9223372036854775808").to_i64(), Some(i64::MIN));
+        assert_eq!(big("9223372036854775808").to_i64(), None);
+        assert_eq!(
+            BigInt::from_f64(2.0_f64.powi(70)),
+            Some(BigInt::from(2).pow(70))
+        );
+        assert_eq!(BigInt::from_f64(-3.0), Some(BigInt::from(-3)));
+        assert_eq!(BigInt::from_f64(0.5), None);
+        assert_eq!(big("-1180591620717411303424").to_f64(), -(2.0_f64.powi(70)));
+    }
+
+    #[test]
+    fn should_match_i64_bitwise_and_shifts() {
+        let values = [0, 1, -1, 5, -6, 1 << 40, -(1 << 40) - 3, i64::MAX, i64::MIN];
+
+        for l in values {
+            for r in values {
+                let (big_l, big_r) = (BigInt::from(l), BigInt::from(r));
+
+                assert_eq!(&big_l & &big_r, BigInt::from(l & r), "{l} & {r}");
+                assert_eq!(&big_l | &big_r, BigInt::from(l | r), "{l} | {r}");
+                assert_eq!(&big_l ^ &big_r, BigInt::from(l ^ r), "{l} ^ {r}");
+            }
+
+            for bits in [0, 1, 31, 32, 33, 63] {
+                assert_eq!(
+                    &BigInt::from(l) >> bits,
+                    BigInt::from(l >> bits),
+                    "{l} >> {bits}"
+                );
+            }
+        }
+
+        assert_eq!(
+            (&BigInt::from(-3) << 100).to_string(),
+            "-3802951800684688204490109616128"
+        );
+        assert_eq!(&BigInt::from(-3) >> 100, BigInt::from(-1));
+        assert_eq!(BigInt::from(2).pow(200).bit_length(), 201);
+    }
+
+    #[test]
+    fn should_order_by_value() {
+        assert!(big("-5") < big("-4"));
+        assert!(big("-100000000000000000000") < big("3"));
+        assert!(big("100000000000000000000") > big("99999999999999999999"));
+    }
+}
@@ -0,0 +1,78 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    rc::Rc,
+};
+
+use super::{function::Function, literal_type::Lit};
+
+/// A class value, created when a `class` declaration is executed. Calling it creates an instance.
+pub struct Class {
+    pub name: String,
+    pub superclass: Option<Rc<Class>>,
+    pub methods: HashMap<String, Rc<Function>>,
+}
+
+impl Class {
+    /// Looks the method up on this class first, then walks up the superclass chain.
+    pub fn find_method(&self, name: &str) -> Option<Rc<Function>> {
+        match self.methods.get(name) {
+            Some(method) => Some(Rc::clone(method)),
+            None => self.superclass.as_ref()?.find_method(name),
+        }
+    }
+}
+
+impl Debug for Class {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<class {}>", self.name)
+    }
+}
+
+impl PartialEq for Class {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+pub struct Instance {
+    pub class: Rc<Class>,
+    fields: RefCell<HashMap<String, Lit>>,
+}
+
+impl Instance {
+    pub fn new(class: Rc<Class>) -> Self {
+        Self {
+            class,
+            fields: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Fields shadow methods. Methods are bound to the instance so they keep their `this` when passed around.
+    pub fn get(instance: &Rc<Instance>, name: &str) -> Option<Lit> {
+        if let Some(value) = instance.fields.borrow().get(name) {
+            return Some(value.clone());
+        }
+
+        let method = instance.class.find_method(name)?;
+
+        Some(Lit::Function(Rc::new(method.bind(Rc::clone(instance)))))
+    }
+
+    pub fn set(&self, name: &str, value: Lit) {
+        self.fields.borrow_mut().insert(name.into(), value);
+    }
+}
+
+impl Debug for Instance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<{} instance>", self.class.name)
+    }
+}
+
+impl PartialEq for Instance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    rc::Rc,
+};
+
+use super::{function::Param, literal_type::Lit};
+
+/// An enum, created when an `enum` declaration is executed. Its variants are read as properties, like `Shape.Circle`.
+pub struct Enum {
+    pub name: String,
+    pub variants: Vec<Rc<Variant>>,
+}
+
+impl Enum {
+    pub fn find_variant(&self, name: &str) -> Option<Rc<Variant>> {
+        self.variants
+            .iter()
+            .find(|variant| variant.name == name)
+            .cloned()
+    }
+
+    /// Reads a variant as a value. A variant with fields is a constructor to call with the payload,
+    /// while a variant without fields is already a value.
+    pub fn get(&self, name: &str) -> Option<Lit> {
+        let variant = self.find_variant(name)?;
+
+        Some(if variant.fields.is_empty() {
+            Lit::EnumValue(Rc::new(EnumValue {
+                variant,
+                values: vec![],
+            }))
+        } else {
+            Lit::Variant(variant)
+        })
+    }
+}
+
+impl Debug for Enum {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<enum {}>", self.name)
+    }
+}
+
+impl PartialEq for Enum {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// One case of an enum, like `Circle(r)`. The field names are used to read the payload, like `shape.r`.
+pub struct Variant {
+    pub enum_name: String,
+    pub name: String,
+    pub fields: Vec<Param>,
+}
+
+impl Variant {
+    pub fn arity(&self) -> usize {
+        self.fields.len()
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<variant {}.{}>", self.enum_name, self.name)
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+/// A value of an enum, like `Shape.Circle(2)`. Values are equal when they have the same variant and equal payloads.
+#[derive(PartialEq)]
+pub struct EnumValue {
+    pub variant: Rc<Variant>,
+    pub values: Vec<Lit>,
+}
+
+impl EnumValue {
+    pub fn get(&self, name: &str) -> Option<Lit> {
+        let position = self
+            .variant
+            .fields
+            .iter()
+            .position(|field| field.name.lexeme == name)?;
+
+        self.values.get(position).cloned()
+    }
+}
+
+impl Debug for EnumValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}.{}", self.variant.enum_name, self.variant.name)
+    }
+}
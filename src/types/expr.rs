@@ -1,48 +1,282 @@
-use super::{literal_type::Lit, token::Tok};
+use std::{fmt::Display, rc::Rc};
+
+use super::{
+    function::Param,
+    literal_type::Lit,
+    pattern::{MatchArm, Pattern},
+    stmt::Stmt,
+    token::Tok,
+    token_type::TokType,
+};
 
 pub enum Expr {
+    // assigns to a variable that already exists, like x = 1. The depth is that of the variable it assigns to
+    Assign {
+        name: Tok,
+        value: Box<Expr>,
+        depth: Option<usize>,
+    },
+    // assigns to several variables at once, like [a, b] = [b, a]. The value is evaluated before any assignment.
+    // There is a depth for each name the pattern binds, in order
+    AssignPattern {
+        pattern: Pattern,
+        equals: Tok,
+        value: Box<Expr>,
+        depths: Vec<Option<usize>>,
+    },
     // two-operands (the items on either side of the operator) like 1 + 1 or 3 != 2
     Binary {
         left: Box<Expr>,
         operator: Tok,
         right: Box<Expr>,
     },
+    // the closing paren is kept to locate call errors
+    Call {
+        callee: Box<Expr>,
+        paren: Tok,
+        args: Vec<Argument>,
+    },
+    // `condition ? then : else`. Only the chosen branch is evaluated
+    Conditional {
+        condition: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+    },
+    // property access, like point.x
+    Get {
+        object: Box<Expr>,
+        name: Tok,
+    },
     Grouping {
         expression: Box<Expr>,
     },
+    // a string with embedded expressions, like "Hi ${name}". Every part is stringified and joined
+    Interpolation {
+        parts: Vec<Expr>,
+    },
+    // element access, like xs[0]. The bracket is kept to locate index errors
+    Index {
+        object: Box<Expr>,
+        bracket: Tok,
+        index: Box<Expr>,
+    },
+    // element assignment, like xs[0] = 1
+    IndexSet {
+        object: Box<Expr>,
+        bracket: Tok,
+        index: Box<Expr>,
+        value: Box<Expr>,
+    },
+    List {
+        elements: Vec<Expr>,
+    },
+    // a map literal, like {"a": 1}. The brace is kept to locate key errors
+    Map {
+        brace: Tok,
+        entries: Vec<(Expr, Expr)>,
+    },
     Literal {
         value: Lit,
     },
-    // something like !x or x++
+    // an anonymous function, like fun (x) { return x * 2; } or (x) => x * 2.
+    // The keyword is `fun` or `=>`. An arrow function's body is a single return statement
+    Lambda {
+        keyword: Tok,
+        params: Rc<Vec<Param>>,
+        body: Rc<Vec<Stmt>>,
+    },
+    // `match subject { pattern => value, ... }`. Gives the value of the first arm whose pattern matches
+    // and whose guard, if any, is truthy. The keyword is kept to locate the error when no arm matches
+    Match {
+        keyword: Tok,
+        subject: Box<Expr>,
+        arms: Vec<MatchArm>,
+    },
+    // property access that gives nil when the object is nil, like user?.name.
+    // The rest of the chain is skipped as well, so user?.name.first is also nil
+    OptionalGet {
+        object: Box<Expr>,
+        name: Tok,
+    },
+    // `and` / `or` / `??`, kept apart from Binary because the right side is only evaluated when needed
+    Logical {
+        left: Box<Expr>,
+        operator: Tok,
+        right: Box<Expr>,
+    },
+    // property assignment, like point.x = 1
+    Set {
+        object: Box<Expr>,
+        name: Tok,
+        value: Box<Expr>,
+    },
+    // a superclass method lookup, like super.init. The depth is that of `super`, and `this` is one scope further in
+    Super {
+        keyword: Tok,
+        method: Tok,
+        depth: Option<usize>,
+    },
+    This {
+        keyword: Tok,
+        depth: Option<usize>,
+    },
+    // something like !x or -x
     Unary {
         operator: Tok,
         right: Box<Expr>,
     },
+    // reads, combines and writes back a variable, property or element, like x += 2, ++x or xs[i]--.
+    // For ++ and -- the value is 1. Postfix forms give the old value, everything else the new one
+    Update {
+        target: Box<Expr>,
+        operator: Tok,
+        value: Box<Expr>,
+        postfix: bool,
+    },
+    // a variable read. The depth is how many scopes out from the current one it was declared in,
+    // worked out by the parser. None means a global, looked up when the code runs
     Variable {
         name: Tok,
+        depth: Option<usize>,
     },
 }
 
-impl Expr {
-    #[allow(dead_code)]
-    pub fn to_string(&self) -> String {
+// an argument of a call, like f(x), f(name: x) or f(..list)
+pub enum Argument {
+    Positional(Expr),
+    Named { name: Tok, value: Expr },
+    // a list whose elements are passed as positional arguments
+    Spread(Expr),
+}
+
+impl Display for Argument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Self::Positional(value) => write!(f, "{}", value),
+            Self::Named { name, value } => write!(f, "{}: {}", name.lexeme, value),
+            Self::Spread(value) => write!(f, "..{}", value),
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Assign { name, value, .. } => write!(f, "(= {} {})", name.lexeme, value),
+            Self::AssignPattern { pattern, value, .. } => write!(f, "(= {} {})", pattern, value),
             Self::Binary {
                 left,
                 operator,
                 right,
-            } => format!(
-                "({} {} {})",
-                operator.lexeme,
-                (*left).to_string(),
-                (*right).to_string()
-            ),
-            Self::Grouping { expression } => format!("(group {})", (*expression).to_string()),
-            Self::Literal { value } => value.to_string(),
-            Self::Unary { operator, right } => {
-                format!("({} {})", operator.lexeme, (*right).to_string())
+            } => write!(f, "({} {} {})", operator.lexeme, left, right),
+            Self::Call { callee, args, .. } => {
+                write!(f, "(call {}", callee)?;
+
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+
+                write!(f, ")")
+            }
+            Self::Conditional {
+                condition,
+                then_branch,
+                else_branch,
+            } => write!(f, "(?: {} {} {})", condition, then_branch, else_branch),
+            Self::Get { object, name } => write!(f, "(. {} {})", object, name.lexeme),
+            Self::OptionalGet { object, name } => write!(f, "(?. {} {})", object, name.lexeme),
+            Self::Grouping { expression } => write!(f, "(group {})", expression),
+            Self::Interpolation { parts } => {
+                write!(f, "(interpolate")?;
+
+                for part in parts {
+                    write!(f, " {}", part)?;
+                }
+
+                write!(f, ")")
+            }
+            Self::Index { object, index, .. } => write!(f, "([] {} {})", object, index),
+            Self::IndexSet {
+                object,
+                index,
+                value,
+                ..
+            } => write!(f, "(= ([] {} {}) {})", object, index, value),
+            Self::List { elements } => {
+                write!(f, "(list")?;
+
+                for element in elements {
+                    write!(f, " {}", element)?;
+                }
+
+                write!(f, ")")
+            }
+            Self::Map { entries, .. } => {
+                write!(f, "(map")?;
+
+                for (key, value) in entries {
+                    write!(f, " ({} {})", key, value)?;
+                }
+
+                write!(f, ")")
             }
-            Self::Variable { name } => format!("(var {})", name.lexeme),
+            Self::Literal { value } => write!(f, "{}", value),
+            Self::Lambda { params, .. } => {
+                write!(f, "(fun (")?;
+
+                for (position, param) in params.iter().enumerate() {
+                    if position > 0 {
+                        write!(f, " ")?;
+                    }
+
+                    write!(f, "{}", param)?;
+                }
+
+                write!(f, "))")
+            }
+            Self::Match { subject, arms, .. } => {
+                write!(f, "(match {}", subject)?;
+
+                for arm in arms {
+                    write!(f, " ({}", arm.pattern)?;
+
+                    if let Some(guard) = &arm.guard {
+                        write!(f, " if {}", guard)?;
+                    }
+
+                    write!(f, " {})", arm.body)?;
+                }
+
+                write!(f, ")")
+            }
+            Self::Logical {
+                left,
+                operator,
+                right,
+            } => write!(f, "({} {} {})", operator.lexeme, left, right),
+            Self::Set {
+                object,
+                name,
+                value,
+            } => write!(f, "(= (. {} {}) {})", object, name.lexeme, value),
+            Self::Super { method, .. } => write!(f, "(super {})", method.lexeme),
+            Self::This { .. } => write!(f, "this"),
+            Self::Unary { operator, right } => write!(f, "({} {})", operator.lexeme, right),
+            Self::Update {
+                target,
+                operator,
+                value,
+                postfix,
+            } => match operator.token_type {
+                TokType::PlusPlus | TokType::MinusMinus if *postfix => {
+                    write!(f, "(post{} {})", operator.lexeme, target)
+                }
+                TokType::PlusPlus | TokType::MinusMinus => {
+                    write!(f, "({} {})", operator.lexeme, target)
+                }
+                _ => write!(f, "({} {} {})", operator.lexeme, target, value),
+            },
+            Self::Variable { name, .. } => write!(f, "(var {})", name.lexeme),
         }
     }
 }
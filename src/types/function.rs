@@ -0,0 +1,169 @@
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    rc::Rc,
+};
+
+use crate::pipeline::environment::Environment;
+
+use super::{class::Instance, expr::Expr, literal_type::Lit, stmt::Stmt, token::Tok};
+
+/// A declared parameter, like `name`, `greeting = "hi"` or `..rest`. Shown that way in argument errors,
+/// except that a default which is not a literal is shown as `…`.
+/// Only the last parameter can be a rest parameter, which collects the extra positional arguments into a list.
+pub struct Param {
+    pub name: Tok,
+    /// Evaluated on each call that leaves the parameter out, in a scope that already holds the earlier parameters.
+    pub default: Option<Expr>,
+    pub rest: bool,
+}
+
+impl Param {
+    pub fn new(name: Tok) -> Self {
+        Self {
+            name,
+            default: None,
+            rest: false,
+        }
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        if self.rest {
+            write!(f, "..")?;
+        }
+
+        write!(f, "{}", self.name.lexeme)?;
+
+        match &self.default {
+            Some(Expr::Literal { value }) => {
+                write!(f, " = ")?;
+                value.fmt_nested(f)
+            }
+            // The source of anything more involved is not kept, so it is only marked as present
+            Some(_) => write!(f, " = …"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Formats what a call expects, like `greet(name, greeting = "hi")`, for argument errors.
+fn signature(name: &str, params: &[Param]) -> String {
+    let params: Vec<String> = params.iter().map(|param| param.to_string()).collect();
+
+    format!("{}({})", name, params.join(", "))
+}
+
+/// The arguments of a call. Spread arguments have already been expanded into the positional ones.
+#[derive(Default)]
+pub struct Arguments {
+    pub positional: Vec<Lit>,
+    pub named: Vec<(Tok, Lit)>,
+}
+
+impl Arguments {
+    pub fn len(&self) -> usize {
+        self.positional.len() + self.named.len()
+    }
+
+    /// Lines the arguments up with the parameters. Positional arguments fill the parameters in order, with any
+    /// extras going to the rest parameter, and named arguments fill the parameter with that name.
+    /// Gives a value for each parameter, or None where the parameter's default is to be used.
+    /// The error describes the mismatch and ends with the signature of the callee, which is named `name`.
+    pub fn bind(self, name: &str, params: &[Param]) -> Result<Vec<Option<Lit>>, String> {
+        self.bind_values(params)
+            .map_err(|reason| format!("{}. Expected {}.", reason, signature(name, params)))
+    }
+
+    fn bind_values(self, params: &[Param]) -> Result<Vec<Option<Lit>>, String> {
+        let given = self.len();
+        let rest = params.iter().position(|param| param.rest);
+        let mut positional = self.positional.into_iter();
+
+        let mut values: Vec<Option<Lit>> = positional
+            .by_ref()
+            .take(rest.unwrap_or(params.len()))
+            .map(Some)
+            .collect();
+
+        values.resize(params.len(), None);
+
+        let extra: Vec<Lit> = positional.collect();
+
+        match rest {
+            Some(position) => values[position] = Some(Lit::List(Rc::new(RefCell::new(extra)))),
+            None if !extra.is_empty() => {
+                let noun = if given == 1 { "argument" } else { "arguments" };
+
+                return Err(format!("Got {} {}", given, noun));
+            }
+            None => (),
+        }
+
+        for (name, value) in self.named {
+            let position = params
+                .iter()
+                .position(|param| !param.rest && param.name.lexeme == name.lexeme)
+                .ok_or_else(|| format!("Unexpected argument {}", name.lexeme))?;
+
+            if values[position].is_some() {
+                return Err(format!("Argument {} was given twice", name.lexeme));
+            }
+
+            values[position] = Some(value);
+        }
+
+        let missing = params
+            .iter()
+            .zip(&values)
+            .find(|(param, value)| value.is_none() && param.default.is_none());
+
+        if let Some((param, _)) = missing {
+            return Err(format!("Missing argument {}", param.name.lexeme));
+        }
+
+        Ok(values)
+    }
+}
+
+/// A user-defined function, created when a `fun` declaration is executed.
+pub struct Function {
+    pub name: Tok,
+    pub params: Rc<Vec<Param>>,
+    pub body: Rc<Vec<Stmt>>,
+    /// The scope the function was declared in. Calls run in a scope nested inside it.
+    pub closure: Rc<RefCell<Environment>>,
+    /// Initializers always give back `this`, whether they return early or run to the end.
+    pub is_initializer: bool,
+}
+
+impl Function {
+    /// Creates a copy of this method whose scope has `this` defined as the given instance.
+    pub fn bind(&self, instance: Rc<Instance>) -> Function {
+        let mut environment = Environment::new_enclosed(Rc::clone(&self.closure));
+
+        environment.define("this", Lit::Instance(instance));
+
+        Function {
+            name: self.name.clone(),
+            params: Rc::clone(&self.params),
+            body: Rc::clone(&self.body),
+            closure: Rc::new(RefCell::new(environment)),
+            is_initializer: self.is_initializer,
+        }
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "<fn {}>", self.name.lexeme)
+    }
+}
+
+// Functions are only equal to themselves, never to a separate declaration with the same shape.
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
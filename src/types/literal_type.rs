@@ -1,9 +1,139 @@
-#[derive(Debug, PartialEq, Clone)]
+use std::{cell::RefCell, cmp::Ordering, rc::Rc, thread::LocalKey};
+
+use crate::error::RuntimeError;
+
+use super::{
+    bigint::BigInt,
+    class::{Class, Instance},
+    enumeration::{Enum, EnumValue, Variant},
+    function::Function,
+    map::Map,
+    number::compare_numbers,
+};
+
+#[derive(Debug, Clone)]
 pub enum Lit {
     String(String),
+    // a float. Literals with a fraction or exponent, and results of arithmetic involving one
     Number(f64),
+    // an exact integer. Literals without a fraction or exponent
+    Integer(i64),
+    // an exact integer too big for an i64. Smaller values are always held as Integer
+    BigInteger(BigInt),
     Bool(bool),
     Nil,
+    Function(Rc<Function>),
+    Class(Rc<Class>),
+    Instance(Rc<Instance>),
+    Enum(Rc<Enum>),
+    // a variant with fields, called to construct a value. Variants without fields are read as values directly
+    Variant(Rc<Variant>),
+    // a value of an enum, like Shape.Circle(2)
+    EnumValue(Rc<EnumValue>),
+    // lists and maps are shared so that every reference sees index assignments
+    List(Rc<RefCell<Vec<Lit>>>),
+    Map(Rc<RefCell<Map>>),
+    // a caught runtime error, with `message` and `line` properties
+    Error(Rc<RuntimeError>),
+}
+
+impl Lit {
+    /// The single truthiness rule used by conditions and the `!` operator.
+    /// `nil`, `false`, `0`, `NaN`, the empty string and empty lists and maps are falsy.
+    /// Everything else is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Lit::String(s) => !s.is_empty(),
+            Lit::Number(n) => *n != 0.0 && !n.is_nan(),
+            Lit::Integer(i) => *i != 0,
+            Lit::BigInteger(i) => !i.is_zero(),
+            Lit::Bool(b) => *b,
+            Lit::Nil => false,
+            Lit::List(list) => !list.borrow().is_empty(),
+            Lit::Map(map) => !map.borrow().is_empty(),
+            Lit::Function(_)
+            | Lit::Class(_)
+            | Lit::Instance(_)
+            | Lit::Enum(_)
+            | Lit::Variant(_)
+            | Lit::EnumValue(_)
+            | Lit::Error(_) => true,
+        }
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Lit::Number(_) | Lit::Integer(_) | Lit::BigInteger(_))
+    }
+
+    /// Formats a value held inside a list or map. Strings are quoted so that ["a, b"] and ["a", "b"] print differently.
+    pub fn fmt_nested(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Lit::String(s) => write!(f, "\"{}\"", s),
+            _ => write!(f, "{}", self),
+        }
+    }
+}
+
+thread_local! {
+    // The lists and maps being printed further up the stack, so a list that contains itself prints as [...]
+    static PRINTING: RefCell<Vec<*const ()>> = const { RefCell::new(vec![]) };
+    // The pairs of lists and maps being compared further up the stack. Meeting a pair again means the
+    // comparison has gone round a cycle, which holds no difference of its own
+    static COMPARING: RefCell<Vec<(*const (), *const ())>> = const { RefCell::new(vec![]) };
+}
+
+/// Runs `visit` with `key` marked as in progress, or returns None if it already is.
+fn visit_once<K: PartialEq, T>(
+    in_progress: &'static LocalKey<RefCell<Vec<K>>>,
+    key: K,
+    visit: impl FnOnce() -> T,
+) -> Option<T> {
+    if in_progress.with_borrow(|keys| keys.contains(&key)) {
+        return None;
+    }
+
+    in_progress.with_borrow_mut(|keys| keys.push(key));
+
+    let result = visit();
+
+    in_progress.with_borrow_mut(|keys| keys.pop());
+
+    Some(result)
+}
+
+// Numbers are equal by value whatever their kind, so `1 == 1.0`.
+// Functions, classes, instances, enums and errors are only equal to themselves.
+// Lists, maps and enum values compare their contents, and lists and maps that contain themselves
+// are equal when they match everywhere outside the cycle.
+impl PartialEq for Lit {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Lit::String(l), Lit::String(r)) => l == r,
+            (Lit::Bool(l), Lit::Bool(r)) => l == r,
+            (Lit::Nil, Lit::Nil) => true,
+            (Lit::Function(l), Lit::Function(r)) => l == r,
+            (Lit::Class(l), Lit::Class(r)) => l == r,
+            (Lit::Instance(l), Lit::Instance(r)) => l == r,
+            (Lit::Enum(l), Lit::Enum(r)) => l == r,
+            (Lit::Variant(l), Lit::Variant(r)) => l == r,
+            (Lit::EnumValue(l), Lit::EnumValue(r)) => l == r,
+            (Lit::List(l), Lit::List(r)) => {
+                let key = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+
+                visit_once(&COMPARING, key, || l == r).unwrap_or(true)
+            }
+            (Lit::Map(l), Lit::Map(r)) => {
+                let key = (Rc::as_ptr(l) as *const (), Rc::as_ptr(r) as *const ());
+
+                visit_once(&COMPARING, key, || l == r).unwrap_or(true)
+            }
+            (Lit::Error(l), Lit::Error(r)) => Rc::ptr_eq(l, r),
+            (l, r) if l.is_number() && r.is_number() => {
+                compare_numbers(l, r) == Some(Ordering::Equal)
+            }
+            _ => false,
+        }
+    }
 }
 
 impl std::fmt::Display for Lit {
@@ -11,8 +141,65 @@ impl std::fmt::Display for Lit {
         match self {
             Lit::String(s) => write!(f, "{}", s),
             Lit::Number(n) => write!(f, "{}", n),
+            Lit::Integer(i) => write!(f, "{}", i),
+            Lit::BigInteger(i) => write!(f, "{}", i),
             Lit::Bool(b) => write!(f, "{}", b),
             Lit::Nil => write!(f, "nil"),
+            Lit::Function(function) => write!(f, "{:?}", function),
+            Lit::Class(class) => write!(f, "{:?}", class),
+            Lit::Instance(instance) => write!(f, "{:?}", instance),
+            Lit::Enum(enumeration) => write!(f, "{:?}", enumeration),
+            Lit::Variant(variant) => write!(f, "{:?}", variant),
+            Lit::EnumValue(value) => {
+                write!(f, "{:?}", value)?;
+
+                if value.values.is_empty() {
+                    return Ok(());
+                }
+
+                write!(f, "(")?;
+
+                for (index, item) in value.values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    item.fmt_nested(f)?;
+                }
+
+                write!(f, ")")
+            }
+            Lit::Error(error) => write!(f, "{}", error.message),
+            Lit::List(list) => visit_once(&PRINTING, Rc::as_ptr(list) as *const (), || {
+                write!(f, "[")?;
+
+                for (index, item) in list.borrow().iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    item.fmt_nested(f)?;
+                }
+
+                write!(f, "]")
+            })
+            .unwrap_or_else(|| write!(f, "[...]")),
+            Lit::Map(map) => visit_once(&PRINTING, Rc::as_ptr(map) as *const (), || {
+                write!(f, "{{")?;
+
+                for (index, (key, value)) in map.borrow().iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    key.to_lit().fmt_nested(f)?;
+                    write!(f, ": ")?;
+                    value.fmt_nested(f)?;
+                }
+
+                write!(f, "}}")
+            })
+            .unwrap_or_else(|| write!(f, "{{...}}")),
         }
     }
 }
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use super::{bigint::BigInt, literal_type::Lit};
+
+/// The hashable form of a value that can be used as a map key.
+/// `Lit` cannot be hashed directly because of its `f64` numbers, so floats are keyed by their bits.
+/// Whole floats are keyed as integers, since `1 == 1.0` must find the same entry.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum MapKey {
+    String(String),
+    Number(u64),
+    Integer(i64),
+    BigInteger(BigInt),
+    Bool(bool),
+    Nil,
+}
+
+impl MapKey {
+    /// Returns None for values that cannot be keys: `NaN`, which never equals itself, and mutable values.
+    pub fn from_lit(value: &Lit) -> Option<Self> {
+        match value {
+            Lit::String(s) => Some(Self::String(s.clone())),
+            Lit::Number(n) if n.is_nan() => None,
+            // Also covers -0.0, which must share a key with 0
+            Lit::Number(n) if n.fract() == 0.0 => Self::from_lit(&Lit::from(BigInt::from_f64(*n)?)),
+            Lit::Number(n) => Some(Self::Number(n.to_bits())),
+            Lit::Integer(i) => Some(Self::Integer(*i)),
+            Lit::BigInteger(i) => Some(Self::BigInteger(i.clone())),
+            Lit::Bool(b) => Some(Self::Bool(*b)),
+            Lit::Nil => Some(Self::Nil),
+            _ => None,
+        }
+    }
+
+    pub fn to_lit(&self) -> Lit {
+        match self {
+            Self::String(s) => Lit::String(s.clone()),
+            Self::Number(bits) => Lit::Number(f64::from_bits(*bits)),
+            Self::Integer(i) => Lit::Integer(*i),
+            Self::BigInteger(i) => Lit::BigInteger(i.clone()),
+            Self::Bool(b) => Lit::Bool(*b),
+            Self::Nil => Lit::Nil,
+        }
+    }
+}
+
+/// A map that remembers the order its keys were first inserted in.
+#[derive(Debug, Default)]
+pub struct Map {
+    entries: Vec<(MapKey, Lit)>,
+    /// Position of each key in `entries`.
+    indices: HashMap<MapKey, usize>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<&Lit> {
+        self.indices.get(key).map(|&index| &self.entries[index].1)
+    }
+
+    /// Overwriting an existing key keeps its original position.
+    pub fn insert(&mut self, key: MapKey, value: Lit) {
+        match self.indices.get(&key) {
+            Some(&index) => self.entries[index].1 = value,
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &(MapKey, Lit)> {
+        self.entries.iter()
+    }
+}
+
+// Two maps are equal when they hold the same entries, whatever order they were inserted in.
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
@@ -1,11 +1,25 @@
+mod bigint;
+mod class;
+mod enumeration;
 mod expr;
+mod function;
 mod literal_type;
+mod map;
+mod number;
+mod pattern;
 mod stmt;
 mod token;
 mod token_type;
 
+pub use bigint::*;
+pub use class::*;
+pub use enumeration::*;
 pub use expr::*;
+pub use function::*;
 pub use literal_type::*;
+pub use map::*;
+pub use number::*;
+pub use pattern::*;
 pub use stmt::*;
 pub use token::*;
 pub use token_type::*;
@@ -0,0 +1,311 @@
+use std::cmp::Ordering;
+
+use super::{bigint::BigInt, literal_type::Lit, token_type::TokType};
+
+// Numbers come in two kinds: exact integers and floats.
+// Integers are `Lit::Integer` while they fit an i64, and promote to `Lit::BigInteger` when they outgrow it,
+// so integer arithmetic never loses precision. Results that fit an i64 again are demoted back.
+// As soon as a float is involved the integer is promoted and the operation is done in floating point.
+
+impl From<BigInt> for Lit {
+    fn from(value: BigInt) -> Self {
+        match value.to_i64() {
+            Some(integer) => Lit::Integer(integer),
+            None => Lit::BigInteger(value),
+        }
+    }
+}
+
+/// Orders two numbers of either kind. Integers are never rounded to floats on the way,
+/// so large integers still compare exactly. Returns None if either side is not a number or is NaN.
+pub fn compare_numbers(left: &Lit, right: &Lit) -> Option<Ordering> {
+    match (left, right) {
+        (Lit::Integer(l), Lit::Integer(r)) => Some(l.cmp(r)),
+        (Lit::Number(l), Lit::Number(r)) => l.partial_cmp(r),
+        (Lit::Number(_), r) => compare_numbers(r, left).map(Ordering::reverse),
+        (l, Lit::Number(r)) => compare_integer_to_float(&to_big(l)?, *r),
+        (l, r) => Some(to_big(l)?.cmp(&to_big(r)?)),
+    }
+}
+
+fn compare_integer_to_float(integer: &BigInt, float: f64) -> Option<Ordering> {
+    if float.is_nan() {
+        return None;
+    }
+
+    if float.is_infinite() {
+        return Some(if float > 0.0 {
+            Ordering::Less
+        } else {
+            Ordering::Greater
+        });
+    }
+
+    let whole = float.trunc();
+
+    Some(
+        integer
+            .cmp(&BigInt::from_f64(whole)?)
+            .then_with(|| 0.0.partial_cmp(&(float - whole)).unwrap_or(Ordering::Equal)),
+    )
+}
+
+/// Applies an arithmetic or bitwise operator to two numbers.
+///
+/// - Dividing two integers gives an integer when the division is exact and a float otherwise,
+///   so `6 / 3` is `2` and `7 / 2` is `3.5`.
+/// - `%` takes the sign of the divisor, so `-7 % 3` is `2`. This keeps `i % len` a valid index.
+/// - An integer raised to a negative integer power is a float, so `2 ** -1` is `0.5`.
+/// - The bitwise operators `& | ^ << >>` work on integers as two's complement, and also accept
+///   whole floats. `>>` rounds towards negative infinity.
+///
+/// Integer division or modulo by zero is an error.
+pub fn apply_arithmetic(left: &Lit, operator: TokType, right: &Lit) -> Result<Lit, String> {
+    match (left, right) {
+        (Lit::Integer(l), Lit::Integer(r)) => apply_integer(*l, operator, *r),
+        _ if is_bitwise(operator) => apply_big(&to_whole(left)?, operator, &to_whole(right)?),
+        (Lit::Number(_), _) | (_, Lit::Number(_)) => match (to_float(left), to_float(right)) {
+            (Some(l), Some(r)) => apply_float(l, operator, r),
+            _ => Err(not_numbers()),
+        },
+        _ => match (to_big(left), to_big(right)) {
+            (Some(l), Some(r)) => apply_big(&l, operator, &r),
+            _ => Err(not_numbers()),
+        },
+    }
+}
+
+pub fn negate(value: &Lit) -> Result<Lit, String> {
+    match value {
+        Lit::Integer(integer) => Ok(integer
+            .checked_neg()
+            .map(Lit::Integer)
+            .unwrap_or_else(|| Lit::from(-&BigInt::from(*integer)))),
+        Lit::BigInteger(integer) => Ok(Lit::from(-integer)),
+        Lit::Number(number) => Ok(Lit::Number(-number)),
+        _ => Err("Only numbers can be negated.".to_string()),
+    }
+}
+
+/// The `~` operator. Like the other bitwise operators it needs an integer or a whole float.
+pub fn bitwise_not(value: &Lit) -> Result<Lit, String> {
+    match value {
+        Lit::Integer(integer) => Ok(Lit::Integer(!integer)),
+        _ => Ok(Lit::from(&-&to_whole(value)? - &BigInt::from(1))),
+    }
+}
+
+fn is_bitwise(operator: TokType) -> bool {
+    matches!(
+        operator,
+        TokType::Ampersand
+            | TokType::Pipe
+            | TokType::Caret
+            | TokType::LessLess
+            | TokType::GreaterGreater
+    )
+}
+
+fn to_float(value: &Lit) -> Option<f64> {
+    match value {
+        Lit::Integer(integer) => Some(*integer as f64),
+        Lit::BigInteger(integer) => Some(integer.to_f64()),
+        Lit::Number(number) => Some(*number),
+        _ => None,
+    }
+}
+
+fn to_big(value: &Lit) -> Option<BigInt> {
+    match value {
+        Lit::Integer(integer) => Some(BigInt::from(*integer)),
+        Lit::BigInteger(integer) => Some(integer.clone()),
+        _ => None,
+    }
+}
+
+/// Integers too big for this many bits are refused rather than risk exhausting memory.
+const MAX_INTEGER_BITS: u64 = 1 << 24;
+
+/// Converts a number to an integer for the bitwise operators, which reject fractions.
+fn to_whole(value: &Lit) -> Result<BigInt, String> {
+    match value {
+        Lit::Number(number) => BigInt::from_f64(*number),
+        _ => to_big(value),
+    }
+    .ok_or_else(|| "Bitwise operators need integer operands.".to_string())
+}
+
+fn apply_integer(left: i64, operator: TokType, right: i64) -> Result<Lit, String> {
+    // None means the result overflows, so it is redone with big integers below
+    let result = match operator {
+        TokType::Plus => left.checked_add(right),
+        TokType::Minus => left.checked_sub(right),
+        TokType::Star => left.checked_mul(right),
+        TokType::Slash => {
+            if right == 0 {
+                return Err(division_by_zero(operator));
+            }
+
+            // A remainder of None means i64::MIN / -1
+            match left.checked_rem(right) {
+                Some(0) => left.checked_div(right),
+                Some(_) => return Ok(Lit::Number(left as f64 / right as f64)),
+                None => None,
+            }
+        }
+        TokType::Percent => {
+            if right == 0 {
+                return Err(division_by_zero(operator));
+            }
+
+            left.checked_rem(right).map(|remainder| {
+                if remainder != 0 && (remainder < 0) != (right < 0) {
+                    remainder + right
+                } else {
+                    remainder
+                }
+            })
+        }
+        TokType::StarStar if right < 0 => {
+            return Ok(Lit::Number((left as f64).powf(right as f64)));
+        }
+        TokType::StarStar => u32::try_from(right)
+            .ok()
+            .and_then(|exponent| left.checked_pow(exponent)),
+        TokType::Ampersand => Some(left & right),
+        TokType::Pipe => Some(left | right),
+        TokType::Caret => Some(left ^ right),
+        TokType::LessLess => u32::try_from(right)
+            .ok()
+            .filter(|bits| *bits < 64 && (left << bits) >> bits == left)
+            .map(|bits| left << bits),
+        TokType::GreaterGreater if right >= 0 => Some(left >> right.min(63)),
+        _ => None,
+    };
+
+    match result {
+        Some(integer) => Ok(Lit::Integer(integer)),
+        None => apply_big(&BigInt::from(left), operator, &BigInt::from(right)),
+    }
+}
+
+fn apply_big(left: &BigInt, operator: TokType, right: &BigInt) -> Result<Lit, String> {
+    let result = match operator {
+        TokType::Plus => left + right,
+        TokType::Minus => left - right,
+        TokType::Star => left * right,
+        TokType::Slash => {
+            let (quotient, remainder) = left
+                .div_rem(right)
+                .ok_or_else(|| division_by_zero(operator))?;
+
+            if !remainder.is_zero() {
+                return Ok(Lit::Number(left.to_f64() / right.to_f64()));
+            }
+
+            quotient
+        }
+        TokType::Percent => {
+            let (_, remainder) = left
+                .div_rem(right)
+                .ok_or_else(|| division_by_zero(operator))?;
+
+            if !remainder.is_zero() && remainder.is_negative() != right.is_negative() {
+                &remainder + right
+            } else {
+                remainder
+            }
+        }
+        TokType::StarStar if right.is_negative() => {
+            return Ok(Lit::Number(left.to_f64().powf(right.to_f64())));
+        }
+        TokType::StarStar => {
+            // 0, 1 and -1 stay small whatever the exponent, and past an i64 only its parity matters to them
+            let exponent = match right.to_i64() {
+                Some(exponent) => exponent as u64,
+                None if left.bit_length() <= 1 => {
+                    if right.is_odd() {
+                        1
+                    } else {
+                        2
+                    }
+                }
+                None => return Err(too_large()),
+            };
+
+            if left.bit_length() > 1
+                && (left.bit_length() - 1).saturating_mul(exponent) > MAX_INTEGER_BITS
+            {
+                return Err(too_large());
+            }
+
+            left.pow(exponent)
+        }
+        TokType::Ampersand => left & right,
+        TokType::Pipe => left | right,
+        TokType::Caret => left ^ right,
+        TokType::LessLess => {
+            let bits = shift_amount(right)?;
+
+            if !left.is_zero() && left.bit_length() + bits > MAX_INTEGER_BITS {
+                return Err(too_large());
+            }
+
+            left << bits as usize
+        }
+        TokType::GreaterGreater => {
+            // Any shift past the last bit gives the same result
+            left >> shift_amount(right)?.min(left.bit_length() + 1) as usize
+        }
+        _ => return Err(unsupported_operator(operator)),
+    };
+
+    Ok(Lit::from(result))
+}
+
+fn shift_amount(amount: &BigInt) -> Result<u64, String> {
+    if amount.is_negative() {
+        return Err("Shift amount must not be negative.".to_string());
+    }
+
+    Ok(amount.to_i64().map_or(u64::MAX, |amount| amount as u64))
+}
+
+fn apply_float(left: f64, operator: TokType, right: f64) -> Result<Lit, String> {
+    match operator {
+        TokType::Plus => Ok(Lit::Number(left + right)),
+        TokType::Minus => Ok(Lit::Number(left - right)),
+        TokType::Star => Ok(Lit::Number(left * right)),
+        TokType::Slash => Ok(Lit::Number(left / right)),
+        TokType::Percent => {
+            let remainder = left % right;
+
+            if remainder != 0.0 && (remainder < 0.0) != (right < 0.0) {
+                Ok(Lit::Number(remainder + right))
+            } else {
+                Ok(Lit::Number(remainder))
+            }
+        }
+        TokType::StarStar => Ok(Lit::Number(left.powf(right))),
+        _ => Err(unsupported_operator(operator)),
+    }
+}
+
+fn division_by_zero(operator: TokType) -> String {
+    match operator {
+        TokType::Percent => "Integer modulo by zero.".to_string(),
+        _ => "Integer division by zero.".to_string(),
+    }
+}
+
+fn too_large() -> String {
+    "Integer result is too large.".to_string()
+}
+
+fn not_numbers() -> String {
+    "Arithmetic is only supported between numbers.".to_string()
+}
+
+fn unsupported_operator(operator: TokType) -> String {
+    format!("Unexpected {} operator for number evaluation.", operator)
+}
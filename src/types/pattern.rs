@@ -0,0 +1,432 @@
+use std::{cell::RefCell, cmp::Ordering, fmt::Display, rc::Rc};
+
+use crate::{error::RuntimeError, pipeline::environment::Environment};
+
+use super::{
+    class::Instance, enumeration::Variant, expr::Expr, literal_type::Lit, map::MapKey,
+    number::compare_numbers, token::Tok,
+};
+
+pub enum Pattern {
+    // `_`, matches anything without binding it
+    Wildcard,
+    // a name, matches anything and binds it
+    Binding {
+        name: Tok,
+    },
+    // matches an equal value, like 0, "a", true or nil
+    Literal {
+        value: Lit,
+    },
+    // matches a number between the bounds, like 1..10. The end is only included with `..=`
+    Range {
+        start: Lit,
+        end: Lit,
+        inclusive: bool,
+    },
+    // matches a list element by element, like [first, second].
+    // With a rest pattern, like [first, ..rest], longer lists match too and the rest pattern is given
+    // a list of the remaining elements. A bare `..` is a wildcard rest
+    List {
+        elements: Vec<Pattern>,
+        rest: Option<Box<Pattern>>,
+    },
+    // matches a map or instance that has the named fields, like {name, age} or {name: [first, ..]}.
+    // The shorthand {name} binds the field to its own name. Other fields are ignored
+    Record {
+        fields: Vec<(Tok, Pattern)>,
+    },
+    // matches a value of an enum variant, like Shape.Circle(r) or Shape.Empty.
+    // Without the parentheses a variant with fields matches whatever its payload.
+    // The depth is that of the enum's variable, like Expr::Variable's
+    Variant {
+        enumeration: Tok,
+        name: Tok,
+        fields: Option<Vec<Pattern>>,
+        depth: Option<usize>,
+    },
+}
+
+// one `pattern if guard => body` of a match expression
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expr>,
+    pub body: Expr,
+}
+
+impl Pattern {
+    /// Whether the pattern matches nothing but only binds, so that it matches every value.
+    pub fn is_irrefutable(&self) -> bool {
+        matches!(self, Pattern::Wildcard | Pattern::Binding { .. })
+    }
+
+    /// The names the pattern binds, in the order they appear.
+    pub fn bindings(&self) -> Vec<&Tok> {
+        let mut names = vec![];
+
+        self.collect_bindings(&mut names);
+
+        names
+    }
+
+    fn collect_bindings<'a>(&'a self, names: &mut Vec<&'a Tok>) {
+        match self {
+            Pattern::Binding { name } => names.push(name),
+            Pattern::List { elements, rest } => {
+                elements.iter().chain(rest.as_deref()).for_each(|element| {
+                    element.collect_bindings(names);
+                });
+            }
+            Pattern::Record { fields } => {
+                for (_, pattern) in fields {
+                    pattern.collect_bindings(names);
+                }
+            }
+            Pattern::Variant {
+                fields: Some(fields),
+                ..
+            } => {
+                for field in fields {
+                    field.collect_bindings(names);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Checks a value against the pattern. On a match, the names the pattern binds are pushed onto
+    /// `bindings` with their values. On a mismatch `bindings` may hold some of them and should be discarded.
+    /// Variant patterns look their enum up in the environment, and fail if it has no such variant.
+    pub fn matches(
+        &self,
+        value: &Lit,
+        environment: &Environment,
+        bindings: &mut Vec<(String, Lit)>,
+    ) -> Result<bool, RuntimeError> {
+        Ok(match self {
+            Pattern::Wildcard => true,
+            Pattern::Binding { name } => {
+                bindings.push((name.lexeme.clone(), value.clone()));
+
+                true
+            }
+            Pattern::Literal { value: expected } => value == expected,
+            Pattern::Range {
+                start,
+                end,
+                inclusive,
+            } => {
+                let above_start = matches!(
+                    compare_numbers(value, start),
+                    Some(Ordering::Greater | Ordering::Equal)
+                );
+
+                let below_end = match compare_numbers(value, end) {
+                    Some(Ordering::Less) => true,
+                    Some(Ordering::Equal) => *inclusive,
+                    _ => false,
+                };
+
+                above_start && below_end
+            }
+            Pattern::List { elements, rest } => {
+                let Lit::List(list) = value else {
+                    return Ok(false);
+                };
+
+                let list = list.borrow();
+
+                let fits = match rest {
+                    Some(_) => list.len() >= elements.len(),
+                    None => list.len() == elements.len(),
+                };
+
+                if !fits || !Self::all_match(elements, &list, environment, bindings)? {
+                    return Ok(false);
+                }
+
+                match rest {
+                    Some(rest) => {
+                        let remaining = list[elements.len()..].to_vec();
+
+                        rest.matches(
+                            &Lit::List(Rc::new(RefCell::new(remaining))),
+                            environment,
+                            bindings,
+                        )?
+                    }
+                    None => true,
+                }
+            }
+            Pattern::Record { fields } => {
+                for (key, pattern) in fields {
+                    match Self::field(value, key) {
+                        Some(Some(field)) if pattern.matches(&field, environment, bindings)? => (),
+                        _ => return Ok(false),
+                    }
+                }
+
+                true
+            }
+            Pattern::Variant {
+                enumeration,
+                name,
+                fields,
+                depth,
+            } => {
+                let variant = Self::resolve_variant(enumeration, name, *depth, environment)?;
+
+                let Lit::EnumValue(enum_value) = value else {
+                    return Ok(false);
+                };
+
+                match fields {
+                    _ if !Rc::ptr_eq(&enum_value.variant, &variant) => false,
+                    Some(fields) if fields.len() != variant.arity() => {
+                        return Err(RuntimeError::new(
+                            format!(
+                                "{}.{} has {} fields but the pattern has {}.",
+                                variant.enum_name,
+                                variant.name,
+                                variant.arity(),
+                                fields.len()
+                            ),
+                            name,
+                        ));
+                    }
+                    Some(fields) => {
+                        Self::all_match(fields, &enum_value.values, environment, bindings)?
+                    }
+                    None => true,
+                }
+            }
+        })
+    }
+
+    /// Binds a value to the pattern of a declaration or assignment, like `var [a, ..rest] = list;`.
+    /// Unlike a match, a value that doesn't fit is an error. It names the part of the pattern that failed
+    /// and its position, like `[1]` or `.name`, and is located at the given token.
+    pub fn destructure(
+        &self,
+        value: &Lit,
+        environment: &Environment,
+        bindings: &mut Vec<(String, Lit)>,
+        token: &Tok,
+    ) -> Result<(), RuntimeError> {
+        self.destructure_at(value, environment, bindings, token, "")
+    }
+
+    fn destructure_at(
+        &self,
+        value: &Lit,
+        environment: &Environment,
+        bindings: &mut Vec<(String, Lit)>,
+        token: &Tok,
+        position: &str,
+    ) -> Result<(), RuntimeError> {
+        let mismatch = |reason: String| {
+            let at = if position.is_empty() {
+                String::new()
+            } else {
+                format!(" at position {}", position)
+            };
+
+            RuntimeError::new(
+                format!(
+                    "Cannot destructure {} into {}{}: {}.",
+                    value, self, at, reason
+                ),
+                token,
+            )
+        };
+
+        match self {
+            Pattern::List { elements, rest } => {
+                let Lit::List(list) = value else {
+                    return Err(mismatch("expected a list".to_string()));
+                };
+
+                let list = list.borrow();
+
+                match rest {
+                    Some(_) if list.len() < elements.len() => {
+                        return Err(mismatch(format!(
+                            "expected at least {} elements",
+                            elements.len()
+                        )));
+                    }
+                    None if list.len() != elements.len() => {
+                        return Err(mismatch(format!("expected {} elements", elements.len())));
+                    }
+                    _ => (),
+                }
+
+                for (index, (element, item)) in elements.iter().zip(list.iter()).enumerate() {
+                    let position = format!("{}[{}]", position, index);
+
+                    element.destructure_at(item, environment, bindings, token, &position)?;
+                }
+
+                if let Some(rest) = rest {
+                    let remaining =
+                        Lit::List(Rc::new(RefCell::new(list[elements.len()..].to_vec())));
+                    let position = format!("{}[{}..]", position, elements.len());
+
+                    rest.destructure_at(&remaining, environment, bindings, token, &position)?;
+                }
+
+                Ok(())
+            }
+            Pattern::Record { fields } => {
+                for (key, pattern) in fields {
+                    let field = match Self::field(value, key) {
+                        Some(Some(field)) => field,
+                        Some(None) => return Err(mismatch(format!("missing {}", key.lexeme))),
+                        None => return Err(mismatch("expected a map or an instance".to_string())),
+                    };
+                    let position = format!("{}.{}", position, key.lexeme);
+
+                    pattern.destructure_at(&field, environment, bindings, token, &position)?;
+                }
+
+                Ok(())
+            }
+            _ if self.matches(value, environment, bindings)? => Ok(()),
+            _ => Err(mismatch("the value does not match".to_string())),
+        }
+    }
+
+    /// Reads a field for a record pattern. Maps are read by string key and instances by property.
+    /// Returns None if the value is neither, and Some(None) if it has no such field.
+    fn field(value: &Lit, key: &Tok) -> Option<Option<Lit>> {
+        match value {
+            Lit::Map(map) => Some(
+                map.borrow()
+                    .get(&MapKey::String(key.lexeme.clone()))
+                    .cloned(),
+            ),
+            Lit::Instance(instance) => Some(Instance::get(instance, &key.lexeme)),
+            _ => None,
+        }
+    }
+
+    fn all_match(
+        patterns: &[Pattern],
+        values: &[Lit],
+        environment: &Environment,
+        bindings: &mut Vec<(String, Lit)>,
+    ) -> Result<bool, RuntimeError> {
+        for (pattern, value) in patterns.iter().zip(values) {
+            if !pattern.matches(value, environment, bindings)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    fn resolve_variant(
+        enumeration: &Tok,
+        name: &Tok,
+        depth: Option<usize>,
+        environment: &Environment,
+    ) -> Result<Rc<Variant>, RuntimeError> {
+        match environment.get_at(depth, &enumeration.lexeme) {
+            Some(Lit::Enum(enumeration)) => enumeration
+                .find_variant(&name.lexeme)
+                .ok_or_else(|| RuntimeError::new("Undefined variant.", name)),
+            Some(_) => Err(RuntimeError::new("Only enums have variants.", enumeration)),
+            None => Err(RuntimeError::new(
+                "A variable has been used that has not been defined.",
+                enumeration,
+            )),
+        }
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "_"),
+            Self::Binding { name } => write!(f, "{}", name.lexeme),
+            Self::Literal { value } => write!(f, "{}", value),
+            Self::Range {
+                start,
+                end,
+                inclusive,
+            } => write!(
+                f,
+                "{}{}{}",
+                start,
+                if *inclusive { "..=" } else { ".." },
+                end
+            ),
+            Self::List { elements, rest } => {
+                write!(f, "[")?;
+
+                for (position, element) in elements.iter().enumerate() {
+                    if position > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "{}", element)?;
+                }
+
+                if let Some(rest) = rest {
+                    if !elements.is_empty() {
+                        write!(f, ", ")?;
+                    }
+
+                    match rest.as_ref() {
+                        Self::Wildcard => write!(f, "..")?,
+                        rest => write!(f, "..{}", rest)?,
+                    }
+                }
+
+                write!(f, "]")
+            }
+            Self::Record { fields } => {
+                write!(f, "{{")?;
+
+                for (position, (key, pattern)) in fields.iter().enumerate() {
+                    if position > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    match pattern {
+                        Self::Binding { name } if name.lexeme == key.lexeme => {
+                            write!(f, "{}", key.lexeme)?
+                        }
+                        pattern => write!(f, "{}: {}", key.lexeme, pattern)?,
+                    }
+                }
+
+                write!(f, "}}")
+            }
+            Self::Variant {
+                enumeration,
+                name,
+                fields,
+                ..
+            } => {
+                write!(f, "{}.{}", enumeration.lexeme, name.lexeme)?;
+
+                if let Some(fields) = fields {
+                    write!(f, "(")?;
+
+                    for (position, field) in fields.iter().enumerate() {
+                        if position > 0 {
+                            write!(f, ", ")?;
+                        }
+
+                        write!(f, "{}", field)?;
+                    }
+
+                    write!(f, ")")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
@@ -1,7 +1,71 @@
-use super::{expr::Expr, token::Tok};
+use std::rc::Rc;
+
+use super::{expr::Expr, function::Param, pattern::Pattern, token::Tok};
 
 pub enum Stmt {
-    Expr { expr: Expr },
-    Print { expr: Expr },
-    Var { name: Tok, expr: Expr },
+    Block {
+        stmts: Vec<Stmt>,
+    },
+    Break,
+    // each method is a Stmt::Function
+    Class {
+        name: Tok,
+        // always an Expr::Variable
+        superclass: Option<Expr>,
+        methods: Vec<Stmt>,
+    },
+    Continue,
+    // each variant is its name and the names of its fields, like Circle(r)
+    Enum {
+        name: Tok,
+        variants: Vec<(Tok, Vec<Tok>)>,
+    },
+    Expr {
+        expr: Expr,
+    },
+    // the body is shared with every function value created from this declaration
+    Function {
+        name: Tok,
+        params: Rc<Vec<Param>>,
+        body: Rc<Vec<Stmt>>,
+    },
+    If {
+        condition: Expr,
+        then_branch: Box<Stmt>,
+        else_branch: Option<Box<Stmt>>,
+    },
+    Print {
+        expr: Expr,
+    },
+    Return {
+        expr: Expr,
+    },
+    // the keyword is kept to locate the error if nothing catches it
+    Throw {
+        keyword: Tok,
+        expr: Expr,
+    },
+    // at least one of catch and finally is present. The catch holds the name the error is bound to
+    Try {
+        body: Vec<Stmt>,
+        catch: Option<(Tok, Vec<Stmt>)>,
+        finally: Option<Vec<Stmt>>,
+    },
+    Var {
+        name: Tok,
+        expr: Expr,
+    },
+    // a destructuring declaration, like var [a, ..rest] = list; or var {name, age} = record;
+    // The equals sign is kept to locate the error when the value doesn't fit the pattern
+    VarPattern {
+        pattern: Pattern,
+        equals: Tok,
+        expr: Expr,
+    },
+    // `for` loops are desugared into a while with an increment, so `continue` still runs the increment
+    While {
+        condition: Expr,
+        body: Box<Stmt>,
+        increment: Option<Expr>,
+    },
 }
@@ -7,6 +7,9 @@ pub enum TokType {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
+    Colon,
     Comma,
     Dot,
     Minus,
@@ -14,8 +17,29 @@ pub enum TokType {
     Semicolon,
     Slash,
     Star,
+    Percent,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    Question,
 
     // One or two char tokens
+    QuestionQuestion,
+    QuestionDot,
+    DotDot,
+    DotDotEqual,
+    EqualGreater,
+    PlusPlus,
+    PlusEqual,
+    MinusMinus,
+    MinusEqual,
+    StarEqual,
+    SlashEqual,
+    PercentEqual,
+    StarStar,
+    LessLess,
+    GreaterGreater,
     Bang,
     BangEqual,
     Equal,
@@ -28,23 +52,33 @@ pub enum TokType {
     // Literals
     Identifier,
     String,
+    /// The part of a string before a `${`. The embedded expression's tokens follow it.
+    Interpolation,
     Number,
 
     // Keywords
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
+    Enum,
     False,
+    Finally,
     Fun,
     For,
     If,
+    Match,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
 
@@ -54,7 +88,7 @@ pub enum TokType {
 
 impl Display for TokType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        return write!(
+        write!(
             f,
             "{}",
             match self {
@@ -64,6 +98,9 @@ impl Display for TokType {
                 Self::RightParen => "right paren",
                 Self::LeftBrace => "left brace",
                 Self::RightBrace => "right brace",
+                Self::LeftBracket => "left bracket",
+                Self::RightBracket => "right bracket",
+                Self::Colon => "colon",
                 Self::Comma => "comma",
                 Self::Dot => "full-stop",
                 Self::Minus => "minus",
@@ -71,6 +108,27 @@ impl Display for TokType {
                 Self::Semicolon => "semicolon",
                 Self::Slash => "slash",
                 Self::Star => "asterisk",
+                Self::Percent => "percent",
+                Self::Ampersand => "ampersand",
+                Self::Pipe => "pipe",
+                Self::Caret => "caret",
+                Self::Tilde => "tilde",
+                Self::Question => "question mark",
+                Self::QuestionQuestion => "double question mark",
+                Self::QuestionDot => "question mark full-stop",
+                Self::DotDot => "double full-stop",
+                Self::DotDotEqual => "double full-stop equal",
+                Self::EqualGreater => "arrow",
+                Self::PlusPlus => "increment",
+                Self::PlusEqual => "plus equal",
+                Self::MinusMinus => "decrement",
+                Self::MinusEqual => "minus equal",
+                Self::StarEqual => "asterisk equal",
+                Self::SlashEqual => "slash equal",
+                Self::PercentEqual => "percent equal",
+                Self::StarStar => "double asterisk",
+                Self::LessLess => "double less-than",
+                Self::GreaterGreater => "double bigger-than",
                 Self::BangEqual => "not equal",
                 Self::Equal => "equal",
                 Self::EqualEqual => "double equal",
@@ -80,25 +138,34 @@ impl Display for TokType {
                 Self::LessEqual => "less-than or equal",
                 Self::Identifier => "identifier",
                 Self::String => "string",
+                Self::Interpolation => "interpolated string",
                 Self::Number => "number",
+                Self::Break => "break",
+                Self::Catch => "catch",
                 Self::Class => "class",
+                Self::Continue => "continue",
                 Self::Else => "else",
+                Self::Enum => "enum",
                 Self::False => "false",
+                Self::Finally => "finally",
                 Self::Fun => "function",
                 Self::For => "for",
                 Self::If => "if",
+                Self::Match => "match",
                 Self::Nil => "nil",
                 Self::Or => "or",
                 Self::Print => "print",
                 Self::Return => "return",
                 Self::Super => "super",
                 Self::This => "this",
+                Self::Throw => "throw",
                 Self::True => "true",
+                Self::Try => "try",
                 Self::Var => "variable",
                 Self::While => "while",
                 Self::Eof => "end of file",
                 Self::None => "none",
             }
-        );
+        )
     }
 }
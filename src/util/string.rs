@@ -1,13 +1,14 @@
 pub fn is_digit(character: char) -> bool {
-    character.is_digit(10)
+    character.is_ascii_digit()
 }
 
+/// Whether the character can start an identifier. `_` counts, so `_` alone and `snake_case` are identifiers.
 pub fn is_alpha(character: char) -> bool {
-    character.is_alphabetic()
+    character.is_alphabetic() || character == '_'
 }
 
 pub fn is_alphanumeric(character: char) -> bool {
-    character.is_alphanumeric()
+    character.is_alphanumeric() || character == '_'
 }
 
 pub fn parse_string(string: &str) -> Option<f64> {